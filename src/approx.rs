@@ -0,0 +1,96 @@
+use crate::float::Float;
+
+/// Approximate comparisons with an explicit tolerance. `PartialEq` on the
+/// math types compares against a fixed absolute epsilon, which is too strict
+/// for large coordinates and too loose for tiny ones.
+///
+/// Types compare component by component and are only equal when every pair
+/// of components is.
+pub trait ApproxEq {
+    type Scalar: Float;
+
+    /// Whether `eq` holds for every pair of corresponding components.
+    fn all_components(&self, other: &Self, eq: impl Fn(Self::Scalar, Self::Scalar) -> bool)
+        -> bool;
+
+    /// Components differ by at most `epsilon`.
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Scalar) -> bool {
+        self.all_components(other, |a, b| a == b || (a - b).abs() <= epsilon)
+    }
+
+    /// Components differ by at most `epsilon`, or by at most `max_relative`
+    /// times the larger magnitude of the two. The absolute `epsilon` covers
+    /// values near 0, where any relative difference is large.
+    fn relative_eq(&self, other: &Self, epsilon: Self::Scalar, max_relative: Self::Scalar) -> bool {
+        self.all_components(other, |a, b| {
+            let difference = (a - b).abs();
+            a == b || difference <= epsilon || difference <= max_relative * a.abs().max(b.abs())
+        })
+    }
+
+    /// Components differ by at most `epsilon`, or have at most `max_ulps`
+    /// representable values between them. Values with different signs only
+    /// pass through `epsilon`.
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Scalar, max_ulps: u64) -> bool {
+        self.all_components(other, |a, b| {
+            if a == b || (a - b).abs() <= epsilon {
+                return true;
+            }
+            let same_sign = (a < Self::Scalar::ZERO) == (b < Self::Scalar::ZERO);
+            !a.is_nan() && !b.is_nan() && same_sign && a.ulps_between(b) <= max_ulps
+        })
+    }
+
+    /// Compares components with `==`, without any tolerance. 0 and -0 are
+    /// equal, NaN isn't equal to anything.
+    fn exact_eq(&self, other: &Self) -> bool {
+        self.all_components(other, |a, b| a == b)
+    }
+}
+
+impl<T: Float> ApproxEq for T {
+    type Scalar = T;
+
+    fn all_components(&self, other: &Self, eq: impl Fn(T, T) -> bool) -> bool {
+        eq(*self, *other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compare_with_absolute_tolerance() {
+        assert!(1.0.abs_diff_eq(&1.05, 0.1));
+        assert!(!1.0.abs_diff_eq(&1.2, 0.1));
+        assert!(f64::INFINITY.abs_diff_eq(&f64::INFINITY, 0.0));
+        assert!(!f64::NAN.abs_diff_eq(&f64::NAN, 1.0));
+    }
+
+    #[test]
+    fn should_compare_with_relative_tolerance() {
+        assert!(1e9.relative_eq(&(1e9 + 1.0), 1e-12, 1e-8));
+        assert!(!1e9.relative_eq(&(1e9 + 100.0), 1e-12, 1e-8));
+        assert!(0.0.relative_eq(&1e-13, 1e-12, 1e-8));
+        assert!(!0.0.relative_eq(&1e-11, 1e-12, 1e-8));
+    }
+
+    #[test]
+    fn should_compare_with_ulps() {
+        let next = f64::from_bits(1.0_f64.to_bits() + 4);
+
+        assert!(1.0.ulps_eq(&next, 0.0, 4));
+        assert!(!1.0.ulps_eq(&next, 0.0, 3));
+        assert!(1e-300.ulps_eq(&-1e-300, 1e-12, 0));
+        assert!(!1e-300.ulps_eq(&-1e-300, 0.0, 1000));
+        assert!(!f32::NAN.ulps_eq(&f32::NAN, 0.0, u64::MAX));
+    }
+
+    #[test]
+    fn should_compare_exactly() {
+        assert!(0.1.exact_eq(&0.1));
+        assert!(0.0.exact_eq(&-0.0));
+        assert!(!0.1.exact_eq(&(0.1 + 1e-17 * 2.0)));
+    }
+}
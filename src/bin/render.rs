@@ -0,0 +1,354 @@
+//! Renders a scene file to an image.
+//!
+//! ```text
+//! render scene.yaml image.png --width 640 --height 480 --samples 2 --threads 8
+//! ```
+
+use ray_tracer_challenge::{
+    Camera, CancellationToken, ImageFormat, Progress, Region, Sampling, Scene, MAX_DEPTH,
+};
+
+use std::env;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage: render <scene> <output> [options]
+
+Renders a .yaml, .yml or .json scene to a .ppm, .png, .hdr or .pfm image.
+
+options:
+  --width <pixels>   image width, the camera's width by default
+  --height <pixels>  image height, the camera's height by default
+  --samples <n>      trace n by n rays through every pixel
+  --threads <n>      number of render threads, all cores by default
+  --depth <n>        maximum number of reflections, 5 by default
+  --crop <x,y,w,h>   render only this rectangle of the image
+  --time-limit <s>   stop after this many seconds and save the rows done so far
+  --help             print this message";
+
+struct Options {
+    scene: String,
+    output: String,
+    width: Option<usize>,
+    height: Option<usize>,
+    samples: Option<usize>,
+    threads: Option<usize>,
+    depth: usize,
+    time_limit: Option<Duration>,
+    crop: Option<Region>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut paths = Vec::new();
+    let mut options = Options {
+        scene: String::new(),
+        output: String::new(),
+        width: None,
+        height: None,
+        samples: None,
+        threads: None,
+        depth: MAX_DEPTH,
+        time_limit: None,
+        crop: None,
+    };
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            paths.push(arg);
+            continue;
+        }
+
+        if arg == "--crop" {
+            options.crop = Some(parse_crop(args.next())?);
+            continue;
+        }
+
+        let mut value = || match args.next() {
+            Some(value) => value
+                .parse::<usize>()
+                .map_err(|_| format!("{} expects a whole number, found {:?}", arg, value)),
+            None => Err(format!("{} expects a value", arg)),
+        };
+        match arg.as_str() {
+            "--width" => options.width = Some(positive(&arg, value()?)?),
+            "--height" => options.height = Some(positive(&arg, value()?)?),
+            "--samples" => options.samples = Some(positive(&arg, value()?)?),
+            "--threads" => options.threads = Some(positive(&arg, value()?)?),
+            "--depth" => options.depth = value()?,
+            "--time-limit" => {
+                options.time_limit = Some(Duration::from_secs(positive(&arg, value()?)? as u64))
+            }
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    match <[String; 2]>::try_from(paths) {
+        Ok([scene, output]) => {
+            options.scene = scene;
+            options.output = output;
+            Ok(options)
+        }
+        Err(_) => Err("expected a scene file and an output path".to_string()),
+    }
+}
+
+fn parse_crop(value: Option<String>) -> Result<Region, String> {
+    let value = value.ok_or("--crop expects a value")?;
+    let numbers = value
+        .split(',')
+        .map(|number| number.trim().parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>();
+    match numbers.as_deref() {
+        Ok([x, y, width, height]) if *width > 0 && *height > 0 => {
+            Ok(Region::new(*x, *y, *width, *height))
+        }
+        _ => Err(format!(
+            "--crop expects x,y,width,height, found {:?}",
+            value
+        )),
+    }
+}
+
+fn positive(option: &str, value: usize) -> Result<usize, String> {
+    if value == 0 {
+        return Err(format!("{} must be at least 1", option));
+    }
+    Ok(value)
+}
+
+/// Resizes the camera keeping its aspect ratio when only one side is given.
+/// Fails if the crop doesn't overlap the resized image.
+fn configure(camera: &Camera, options: &Options) -> Result<Camera, String> {
+    let aspect = camera.hsize as f64 / camera.vsize as f64;
+    let (width, height) = match (options.width, options.height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, ((width as f64 / aspect).round() as usize).max(1)),
+        (None, Some(height)) => (((height as f64 * aspect).round() as usize).max(1), height),
+        (None, None) => (camera.hsize, camera.vsize),
+    };
+
+    let mut configured = Camera::new(width, height, camera.field_of_view);
+    configured.transform = camera.transform;
+    configured.crop = options.crop.or(camera.crop);
+    configured.sampling = match (options.samples, camera.sampling) {
+        (None, sampling) => sampling,
+        (Some(n), Sampling::Grid { .. }) => Sampling::Grid { n },
+        (Some(n), Sampling::Jittered { .. }) => Sampling::Jittered { n },
+        (Some(n), Sampling::Adaptive { threshold, .. }) => Sampling::Adaptive { n, threshold },
+    };
+
+    if configured.region().is_empty() {
+        return Err(format!(
+            "the crop lies outside the {}x{} image",
+            width, height
+        ));
+    }
+    Ok(configured)
+}
+
+fn report(camera: &Camera, progress: &Progress) {
+    let eta = match progress.eta() {
+        Some(eta) => format!("{}s left", eta.as_secs()),
+        None => "estimating".to_string(),
+    };
+    eprint!(
+        "\rrendering {}x{}: {:3.0}%, {}   ",
+        camera.region().width,
+        camera.region().height,
+        progress.fraction() * 100.0,
+        eta
+    );
+    io::stderr().flush().ok();
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    // fail before rendering rather than after
+    ImageFormat::from_path(Path::new(&options.output))?;
+
+    let start = Instant::now();
+    let scene = Scene::load(&options.scene)?;
+    let camera = configure(&scene.camera, options)?;
+    let threads = options
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |threads| threads.get()));
+    eprintln!(
+        "loaded {} ({} objects, {} lights) in {:.2?}",
+        options.scene,
+        scene.world.objects.len(),
+        scene.world.lights.len(),
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let last_percent = AtomicUsize::new(usize::MAX);
+    let cancel = CancellationToken::new();
+    let rendered = camera.render_parallel(
+        threads,
+        |ray| scene.world.color_at_depth(ray, options.depth),
+        |progress| {
+            if matches!(options.time_limit, Some(limit) if progress.elapsed > limit) {
+                cancel.cancel();
+            }
+            let percent = (progress.fraction() * 100.0) as usize;
+            if last_percent.swap(percent, Ordering::Relaxed) != percent {
+                report(&camera, progress);
+            }
+        },
+        &cancel,
+    );
+
+    let image = match rendered {
+        Ok(image) => {
+            eprintln!(
+                "\rrendered {}x{} on {} threads in {:.2?}{:10}",
+                camera.region().width,
+                camera.region().height,
+                threads,
+                start.elapsed(),
+                ""
+            );
+            image
+        }
+        Err(cancelled) => {
+            eprintln!("\r{} after {:.2?}{:10}", cancelled, start.elapsed(), "");
+            cancelled.partial
+        }
+    };
+
+    let start = Instant::now();
+    image.save(&options.output)?;
+    eprintln!("saved {} in {:.2?}", options.output, start.elapsed());
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse_args(args.into_iter()) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Result<Options, String> {
+        parse_args(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn should_parse_paths_and_options() {
+        let options = args("scene.yaml image.png --width 320 --samples 2 --depth 0").unwrap();
+
+        assert_eq!(options.scene, "scene.yaml");
+        assert_eq!(options.output, "image.png");
+        assert_eq!(options.width, Some(320));
+        assert_eq!(options.height, None);
+        assert_eq!(options.samples, Some(2));
+        assert_eq!(options.depth, 0);
+    }
+
+    #[test]
+    fn should_reject_unknown_options_and_missing_values() {
+        assert_eq!(
+            args("scene.yaml image.png --fast").err(),
+            Some("unknown option --fast".to_string())
+        );
+        assert_eq!(
+            args("scene.yaml image.png --width").err(),
+            Some("--width expects a value".to_string())
+        );
+        assert_eq!(
+            args("scene.yaml image.png --threads 0").err(),
+            Some("--threads must be at least 1".to_string())
+        );
+        assert_eq!(
+            args("scene.yaml --width 10").err(),
+            Some("expected a scene file and an output path".to_string())
+        );
+    }
+
+    #[test]
+    fn should_parse_crop() {
+        assert_eq!(
+            parse_crop(Some("1, 2,30,40".to_string())),
+            Ok(Region::new(1, 2, 30, 40))
+        );
+        assert!(parse_crop(None).is_err());
+        assert!(parse_crop(Some("1,2,30".to_string())).is_err());
+        assert!(parse_crop(Some("1,2,0,40".to_string())).is_err());
+        assert!(parse_crop(Some("1,2,-30,40".to_string())).is_err());
+    }
+
+    #[test]
+    fn should_keep_aspect_ratio_when_resizing_one_side() {
+        let camera = Camera::new(200, 100, 1.0);
+        let mut options = args("scene.yaml image.png --width 50").unwrap();
+
+        let configured = configure(&camera, &options).unwrap();
+        assert_eq!((configured.hsize, configured.vsize), (50, 25));
+
+        options.width = None;
+        options.height = Some(30);
+        let configured = configure(&camera, &options).unwrap();
+        assert_eq!((configured.hsize, configured.vsize), (60, 30));
+
+        options.width = Some(10);
+        let configured = configure(&camera, &options).unwrap();
+        assert_eq!((configured.hsize, configured.vsize), (10, 30));
+    }
+
+    #[test]
+    fn should_reject_crop_outside_the_image() {
+        let camera = Camera::new(200, 100, 1.0);
+        let options = args("scene.yaml image.png --width 50 --crop 60,0,10,10").unwrap();
+
+        assert_eq!(
+            configure(&camera, &options).err(),
+            Some("the crop lies outside the 50x25 image".to_string())
+        );
+    }
+
+    #[test]
+    fn should_override_sample_count_of_the_scene() {
+        let mut camera = Camera::new(20, 10, 1.0);
+        camera.sampling = Sampling::Adaptive {
+            n: 2,
+            threshold: 0.1,
+        };
+        let options = args("scene.yaml image.png --samples 4").unwrap();
+
+        let configured = configure(&camera, &options).unwrap();
+
+        assert_eq!(
+            configured.sampling,
+            Sampling::Adaptive {
+                n: 4,
+                threshold: 0.1
+            }
+        );
+        assert_eq!(configured.transform, camera.transform);
+    }
+}
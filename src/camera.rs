@@ -0,0 +1,823 @@
+mod progress;
+mod region;
+
+pub use progress::{CancellationToken, Cancelled, Progress};
+pub use region::Region;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::random::Random;
+use crate::ray::Ray;
+use crate::tuple::Tuple4;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Instant;
+
+/// How many rays are traced through every pixel. `n` is the number of
+/// samples along each axis, so a pixel receives `n * n` rays. An `n` of 0
+/// traces a single ray, like 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Sampling {
+    /// Samples at the centers of a regular `n` by `n` grid.
+    Grid { n: usize },
+    /// Splits the pixel into `n` by `n` cells and samples a random spot in each.
+    Jittered { n: usize },
+    /// Traces one ray per pixel and adds `n` by `n` jittered samples only where
+    /// a neighbouring pixel differs by more than `threshold` in any channel.
+    Adaptive { n: usize, threshold: f64 },
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling::Grid { n: 1 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub hsize: usize,
+    pub vsize: usize,
+    pub field_of_view: f64,
+    pub transform: Matrix<4>,
+    pub sampling: Sampling,
+    /// Renders only this part of the image when set.
+    pub crop: Option<Region>,
+    half_width: f64,
+    half_height: f64,
+    pixel_size: f64,
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f64 / vsize as f64;
+        let (half_width, half_height) = if aspect >= 1.0 {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+
+        Self {
+            hsize,
+            vsize,
+            field_of_view,
+            transform: Matrix::identity(),
+            sampling: Sampling::default(),
+            crop: None,
+            half_width,
+            half_height,
+            pixel_size: half_width * 2.0 / hsize as f64,
+        }
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        self.pixel_size
+    }
+
+    /// Returns the ray through the center of the pixel at `px`, `py`.
+    pub fn ray_for_pixel(&self, px: usize, py: usize) -> Ray {
+        self.ray_through(&self.transform.inverse(), px as f64 + 0.5, py as f64 + 0.5)
+    }
+
+    /// Returns the ray through an arbitrary spot on the canvas, where the pixel
+    /// at `px`, `py` covers `px..px + 1` and `py..py + 1`.
+    fn ray_through(&self, inverse: &Matrix<4>, x: f64, y: f64) -> Ray {
+        let world_x = self.half_width - x * self.pixel_size;
+        let world_y = self.half_height - y * self.pixel_size;
+
+        let pixel = *inverse * Point::new(world_x, world_y, -1.0);
+        let origin = *inverse * Point::new(0.0, 0.0, 0.0);
+        let direction = (pixel - origin).normalize();
+
+        Ray::new(origin, direction)
+    }
+
+    /// Maps world points to homogeneous canvas coordinates. After dividing
+    /// by `w`, x and y are where the point lands on the canvas, with the
+    /// pixel at `px`, `py` covering `px..px + 1` and `py..py + 1`, and z is
+    /// the reciprocal of its depth in front of the camera.
+    pub fn pixel_projection(&self) -> Matrix<4> {
+        // the inverse of `ray_through`, with the depth along -z as w
+        let scale = 1.0 / self.pixel_size;
+        let screen = Matrix::from([
+            [-scale, 0.0, -self.half_width * scale, 0.0],
+            [0.0, -scale, -self.half_height * scale, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0, 0.0],
+        ]);
+        screen * self.transform
+    }
+
+    /// Where `point` lands on the canvas, for drawing overlays on top of a
+    /// render. The position may lie outside the canvas. Returns `None` for
+    /// points behind the camera.
+    pub fn project(&self, point: Point) -> Option<(f64, f64)> {
+        let projected = self.pixel_projection() * Tuple4::from(point);
+        if projected.w <= 0.0 {
+            return None;
+        }
+        let pixel = projected.perspective_divide()?;
+        Some((pixel.x, pixel.y))
+    }
+
+    fn sample_pixel(
+        &self,
+        inverse: &Matrix<4>,
+        px: usize,
+        py: usize,
+        n: usize,
+        jitter: bool,
+        trace: &impl Fn(&Ray) -> Color,
+    ) -> Color {
+        let n = n.max(1);
+        let mut random = Random::new((py * self.hsize + px) as u64);
+        let mut color = Color::new(0.0, 0.0, 0.0);
+
+        for i in 0..n {
+            for j in 0..n {
+                let (dx, dy) = if jitter {
+                    (random.next_f64(), random.next_f64())
+                } else {
+                    (0.5, 0.5)
+                };
+                let x = px as f64 + (i as f64 + dx) / n as f64;
+                let y = py as f64 + (j as f64 + dy) / n as f64;
+                color = color + trace(&self.ray_through(inverse, x, y));
+            }
+        }
+
+        color * (1.0 / (n * n) as f64)
+    }
+
+    /// The part of the image that gets rendered: `crop` kept inside the
+    /// image, or the whole image.
+    pub fn region(&self) -> Region {
+        let image = Region::new(0, 0, self.hsize, self.vsize);
+        match self.crop {
+            Some(crop) => crop.intersect(&image),
+            None => image,
+        }
+    }
+
+    /// The pixels traced by the first pass. Adaptive sampling compares every
+    /// pixel with its neighbours, so it needs a one pixel border around the
+    /// region for crops to match the same pixels of a full render.
+    fn first_pass_region(&self) -> Region {
+        let region = self.region();
+        match self.sampling {
+            Sampling::Adaptive { .. } => region.grow(1, &Region::new(0, 0, self.hsize, self.vsize)),
+            _ => region,
+        }
+    }
+
+    fn to_canvas(&self, region: Region, pixels: &[Color]) -> Canvas {
+        let mut image = Canvas::new(region.width, region.height);
+        for py in 0..region.height {
+            for px in 0..region.width {
+                image.set_pixel(px, py, pixels[px + py * region.width]);
+            }
+        }
+        image
+    }
+
+    /// Renders the image by handing every camera ray to `trace` and averaging
+    /// the returned colors according to `sampling`. With a `crop` the canvas
+    /// only holds the cropped region, pixel `0, 0` being its top left corner.
+    /// Runs the passes of `render_parallel` on the calling thread.
+    pub fn render(&self, trace: impl Fn(&Ray) -> Color + Sync) -> Canvas {
+        // a fresh token is never cancelled, so the render always completes
+        match self.render_parallel(1, trace, |_| {}, &CancellationToken::new()) {
+            Ok(image) => image,
+            Err(cancelled) => cancelled.partial,
+        }
+    }
+
+    /// Same as `render`, but spreads the rows over `threads` threads. After
+    /// every row `progress` is called from the thread that finished it; a
+    /// closure sending to a channel works as well. Once `cancel` is set the
+    /// threads stop picking up rows and the partial image is returned in the
+    /// error.
+    pub fn render_parallel(
+        &self,
+        threads: usize,
+        trace: impl Fn(&Ray) -> Color + Sync,
+        progress: impl Fn(&Progress) + Sync,
+        cancel: &CancellationToken,
+    ) -> Result<Canvas, Cancelled> {
+        let inverse = self.transform.inverse();
+        let region = self.region();
+        let first_region = self.first_pass_region();
+        let (n, jitter, rows_total) = match self.sampling {
+            Sampling::Grid { n } => (n, false, region.height),
+            Sampling::Jittered { n } => (n, true, region.height),
+            Sampling::Adaptive { .. } => (1, false, first_region.height + region.height),
+        };
+        let start = Instant::now();
+        let rows_done = AtomicUsize::new(0);
+        let report = |rows_done| Progress {
+            rows_done,
+            rows_total,
+            elapsed: start.elapsed(),
+        };
+        let row_done = || progress(&report(rows_done.fetch_add(1, Ordering::Relaxed) + 1));
+
+        let mut first_pass =
+            vec![Color::new(0.0, 0.0, 0.0); first_region.width * first_region.height];
+        self.parallel_pass(
+            threads,
+            &first_region,
+            &mut first_pass,
+            |px, py| self.sample_pixel(&inverse, px, py, n, jitter, &trace),
+            &row_done,
+            cancel,
+        );
+
+        // rows the second pass doesn't get to keep their first pass
+        let mut pixels = (region.y..region.y + region.height)
+            .flat_map(|py| (region.x..region.x + region.width).map(move |px| (px, py)))
+            .map(|(px, py)| first_pass[first_region.index(px, py)])
+            .collect::<Vec<Color>>();
+
+        if let Sampling::Adaptive { n, threshold } = self.sampling {
+            if !cancel.is_cancelled() {
+                self.parallel_pass(
+                    threads,
+                    &region,
+                    &mut pixels,
+                    |px, py| {
+                        if self.has_differing_neighbour(
+                            &first_pass,
+                            &first_region,
+                            px,
+                            py,
+                            threshold,
+                        ) {
+                            self.sample_pixel(&inverse, px, py, n, true, &trace)
+                        } else {
+                            first_pass[first_region.index(px, py)]
+                        }
+                    },
+                    &row_done,
+                    cancel,
+                );
+            }
+        }
+
+        let image = self.to_canvas(region, &pixels);
+        let progress = report(rows_done.into_inner());
+        if progress.rows_done < progress.rows_total {
+            return Err(Cancelled {
+                partial: image,
+                progress,
+            });
+        }
+        Ok(image)
+    }
+
+    /// Replaces the rows of `pixels`, a buffer covering `region`, with the
+    /// colors computed by `pixel`, handing out rows to the threads as they
+    /// become idle. Rows not started before `cancel` is set keep their old
+    /// colors.
+    fn parallel_pass(
+        &self,
+        threads: usize,
+        region: &Region,
+        pixels: &mut [Color],
+        pixel: impl Fn(usize, usize) -> Color + Sync,
+        row_done: &(impl Fn() + Sync),
+        cancel: &CancellationToken,
+    ) {
+        let next_row = AtomicUsize::new(0);
+        let work = || {
+            let mut rows = Vec::new();
+            loop {
+                let row = next_row.fetch_add(1, Ordering::Relaxed);
+                if row >= region.height || cancel.is_cancelled() {
+                    return rows;
+                }
+                let colors = (region.x..region.x + region.width)
+                    .map(|px| pixel(px, region.y + row))
+                    .collect::<Vec<Color>>();
+                rows.push((row, colors));
+                row_done();
+            }
+        };
+
+        // a single thread does the work itself instead of spawning one
+        let rows = if threads <= 1 {
+            work()
+        } else {
+            thread::scope(|scope| {
+                let workers = (0..threads).map(|_| scope.spawn(work)).collect::<Vec<_>>();
+
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().unwrap())
+                    .collect::<Vec<(usize, Vec<Color>)>>()
+            })
+        };
+
+        for (row, colors) in rows {
+            pixels[row * region.width..(row + 1) * region.width].copy_from_slice(&colors);
+        }
+    }
+
+    /// Whether a neighbour of `px`, `py` inside the image differs by more
+    /// than `threshold`. `pixels` covers `region`, which has to include
+    /// those neighbours.
+    fn has_differing_neighbour(
+        &self,
+        pixels: &[Color],
+        region: &Region,
+        px: usize,
+        py: usize,
+        threshold: f64,
+    ) -> bool {
+        let color = pixels[region.index(px, py)];
+        let neighbours = [
+            (px.wrapping_sub(1), py),
+            (px + 1, py),
+            (px, py.wrapping_sub(1)),
+            (px, py + 1),
+        ];
+
+        neighbours
+            .iter()
+            .filter(|(x, y)| *x < self.hsize && *y < self.vsize)
+            .map(|(x, y)| pixels[region.index(*x, *y)])
+            .any(|other| {
+                (color.r - other.r).abs() > threshold
+                    || (color.g - other.g).abs() > threshold
+                    || (color.b - other.b).abs() > threshold
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equal;
+    use crate::vector::Vector;
+    use std::f64::consts::PI;
+    use std::sync::{mpsc, Mutex};
+
+    #[test]
+    fn should_construct_a_camera() {
+        let camera = Camera::new(160, 120, PI / 2.0);
+
+        assert_eq!(camera.hsize, 160);
+        assert_eq!(camera.vsize, 120);
+        assert_eq!(camera.field_of_view, PI / 2.0);
+        assert_eq!(camera.transform, Matrix::identity());
+        assert_eq!(camera.sampling, Sampling::Grid { n: 1 });
+    }
+
+    #[test]
+    fn should_calculate_pixel_size_for_horizontal_canvas() {
+        let camera = Camera::new(200, 125, PI / 2.0);
+        assert!(equal(camera.pixel_size(), 0.01));
+    }
+
+    #[test]
+    fn should_calculate_pixel_size_for_vertical_canvas() {
+        let camera = Camera::new(125, 200, PI / 2.0);
+        assert!(equal(camera.pixel_size(), 0.01));
+    }
+
+    #[test]
+    fn should_construct_ray_through_center_of_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+
+        let ray = camera.ray_for_pixel(100, 50);
+
+        assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(ray.direction, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn should_construct_ray_through_corner_of_canvas() {
+        let camera = Camera::new(201, 101, PI / 2.0);
+
+        let ray = camera.ray_for_pixel(0, 0);
+
+        assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(ray.direction, Vector::new(0.66519, 0.33259, -0.66851));
+    }
+
+    #[test]
+    fn should_construct_ray_when_camera_is_transformed() {
+        let mut camera = Camera::new(201, 101, PI / 2.0);
+        camera.transform = Matrix::rotation_y(PI / 4.0) * Matrix::translation(0.0, -2.0, 5.0);
+
+        let ray = camera.ray_for_pixel(100, 50);
+
+        let half_sqrt = 2.0_f64.sqrt() / 2.0;
+        assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
+        assert_eq!(ray.direction, Vector::new(half_sqrt, 0.0, -half_sqrt));
+    }
+
+    #[test]
+    fn should_project_points_onto_the_pixels_their_rays_pass() {
+        let mut camera = Camera::new(160, 90, PI / 3.0);
+        camera.transform = Matrix::view_transform(
+            Point::new(1.0, 2.0, -6.0),
+            Point::new(0.0, 0.5, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+
+        for (px, py) in [(0, 0), (80, 45), (159, 12), (3, 89)] {
+            let ray = camera.ray_for_pixel(px, py);
+            let (x, y) = camera.project(ray.position(7.5)).unwrap();
+
+            assert!(equal(x, px as f64 + 0.5), "{} != {}", x, px);
+            assert!(equal(y, py as f64 + 0.5), "{} != {}", y, py);
+        }
+    }
+
+    #[test]
+    fn should_not_project_points_behind_the_camera() {
+        let camera = Camera::new(10, 10, PI / 2.0);
+
+        assert_eq!(camera.project(Point::new(0.0, 0.0, 1.0)), None);
+        assert_eq!(camera.project(Point::new(0.0, 0.0, 0.0)), None);
+        let (x, y) = camera.project(Point::new(0.0, 0.0, -2.0)).unwrap();
+        assert!(equal(x, 5.0) && equal(y, 5.0));
+    }
+
+    // everything hitting the image plane at x > 0.5 is white, which covers
+    // the left half of pixel 0 in a 2x1 image
+    fn half_plane(ray: &Ray) -> Color {
+        if ray.direction.x / -ray.direction.z > 0.5 {
+            Color::new(1.0, 1.0, 1.0)
+        } else {
+            Color::new(0.0, 0.0, 0.0)
+        }
+    }
+
+    fn split_pixel_camera() -> Camera {
+        // pixel 0 spans x = 1 down to x = 0 on the image plane
+        Camera::new(2, 1, PI / 2.0)
+    }
+
+    #[test]
+    fn should_trace_one_ray_per_pixel_by_default() {
+        let camera = split_pixel_camera();
+        let rays = AtomicUsize::new(0);
+
+        let image = camera.render(|ray| {
+            rays.fetch_add(1, Ordering::Relaxed);
+            half_plane(ray)
+        });
+
+        assert_eq!(rays.load(Ordering::Relaxed), 2);
+        assert_eq!(image.get_pixel(0, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_trace_one_ray_per_pixel_without_samples() {
+        let mut camera = split_pixel_camera();
+        camera.sampling = Sampling::Jittered { n: 0 };
+        let rays = AtomicUsize::new(0);
+
+        let image = camera.render(|ray| {
+            rays.fetch_add(1, Ordering::Relaxed);
+            half_plane(ray)
+        });
+
+        assert_eq!(rays.load(Ordering::Relaxed), 2);
+        assert_eq!(image.get_pixel(1, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_average_grid_samples() {
+        let mut camera = split_pixel_camera();
+        camera.sampling = Sampling::Grid { n: 4 };
+        let rays = AtomicUsize::new(0);
+
+        let image = camera.render(|ray| {
+            rays.fetch_add(1, Ordering::Relaxed);
+            half_plane(ray)
+        });
+
+        assert_eq!(rays.load(Ordering::Relaxed), 32);
+        assert_eq!(image.get_pixel(0, 0), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(image.get_pixel(1, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_keep_jittered_samples_inside_their_pixel() {
+        let mut camera = split_pixel_camera();
+        camera.sampling = Sampling::Jittered { n: 8 };
+
+        let image = camera.render(|ray| {
+            assert!(ray.direction.x / -ray.direction.z <= 1.0);
+            half_plane(ray)
+        });
+
+        let coverage = image.get_pixel(0, 0).r;
+        assert!(coverage > 0.3 && coverage < 0.7);
+        assert_eq!(image.get_pixel(1, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_be_deterministic_when_jittering() {
+        let mut camera = split_pixel_camera();
+        camera.sampling = Sampling::Jittered { n: 3 };
+
+        let first = camera.render(half_plane);
+        let second = camera.render(half_plane);
+
+        assert_eq!(first.get_pixel(0, 0), second.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn should_only_supersample_differing_pixels_adaptively() {
+        let mut camera = Camera::new(4, 4, PI / 2.0);
+        camera.sampling = Sampling::Adaptive {
+            n: 2,
+            threshold: 0.1,
+        };
+        let rays = AtomicUsize::new(0);
+
+        let image = camera.render(|_| {
+            rays.fetch_add(1, Ordering::Relaxed);
+            Color::new(0.5, 0.5, 0.5)
+        });
+
+        assert_eq!(rays.load(Ordering::Relaxed), 16);
+        assert_eq!(image.get_pixel(3, 3), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn should_supersample_edges_adaptively() {
+        let mut camera = Camera::new(4, 1, PI / 2.0);
+        camera.sampling = Sampling::Adaptive {
+            n: 4,
+            threshold: 0.1,
+        };
+        let rays = AtomicUsize::new(0);
+
+        // pixel 0 is white, its neighbour is black, the right half is uniform
+        let image = camera.render(|ray| {
+            rays.fetch_add(1, Ordering::Relaxed);
+            if ray.direction.x / -ray.direction.z > 0.5 {
+                Color::new(1.0, 1.0, 1.0)
+            } else {
+                Color::new(0.0, 0.0, 0.0)
+            }
+        });
+
+        assert_eq!(rays.load(Ordering::Relaxed), 4 + 2 * 16);
+        assert_eq!(image.get_pixel(0, 0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(image.get_pixel(3, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_render_a_world_with_a_camera() {
+        let world = crate::world::tests::default_world();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        camera.transform = Matrix::view_transform(from, to, up);
+
+        let image = camera.render(|ray| world.color_at(ray));
+
+        assert_eq!(image.get_pixel(5, 5), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn should_render_the_same_image_on_several_threads() {
+        let world = crate::world::tests::default_world();
+        let mut camera = Camera::new(11, 11, PI / 2.0);
+        camera.transform = Matrix::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        camera.sampling = Sampling::Adaptive {
+            n: 2,
+            threshold: 0.1,
+        };
+
+        let expected = camera.render(|ray| world.color_at(ray));
+        let image = camera
+            .render_parallel(
+                4,
+                |ray| world.color_at(ray),
+                |_| {},
+                &CancellationToken::new(),
+            )
+            .unwrap();
+
+        for py in 0..11 {
+            for px in 0..11 {
+                assert_eq!(image.get_pixel(px, py), expected.get_pixel(px, py));
+            }
+        }
+    }
+
+    #[test]
+    fn should_report_progress_of_every_row() {
+        let camera = Camera::new(4, 3, PI / 2.0);
+        let reports = Mutex::new(Vec::new());
+
+        camera
+            .render_parallel(
+                2,
+                |_| Color::new(0.0, 0.0, 0.0),
+                |progress| reports.lock().unwrap().push(*progress),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+
+        let mut rows = reports
+            .into_inner()
+            .unwrap()
+            .iter()
+            .map(|progress| (progress.rows_done, progress.rows_total))
+            .collect::<Vec<(usize, usize)>>();
+        rows.sort();
+        assert_eq!(rows, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn should_report_progress_over_a_channel() {
+        let mut camera = Camera::new(4, 3, PI / 2.0);
+        camera.sampling = Sampling::Adaptive {
+            n: 2,
+            threshold: 0.1,
+        };
+        let (sender, receiver) = mpsc::channel();
+
+        camera
+            .render_parallel(
+                3,
+                |_| Color::new(0.0, 0.0, 0.0),
+                |progress| sender.send(*progress).unwrap(),
+                &CancellationToken::new(),
+            )
+            .unwrap();
+        drop(sender);
+
+        let reports = receiver.iter().collect::<Vec<Progress>>();
+        assert_eq!(reports.len(), 6);
+        assert!(reports.iter().all(|progress| progress.rows_total == 6));
+    }
+
+    #[test]
+    fn should_keep_finished_rows_when_cancelled() {
+        let camera = Camera::new(4, 10, PI / 2.0);
+        let cancel = CancellationToken::new();
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        let cancelled = camera
+            .render_parallel(
+                1,
+                |_| white,
+                |progress| {
+                    if progress.rows_done == 3 {
+                        cancel.cancel();
+                    }
+                },
+                &cancel,
+            )
+            .err()
+            .expect("render should be cancelled");
+
+        assert_eq!(cancelled.progress.rows_done, 3);
+        assert_eq!(cancelled.progress.rows_total, 10);
+        for py in 0..10 {
+            let expected = if py < 3 {
+                white
+            } else {
+                Color::new(0.0, 0.0, 0.0)
+            };
+            assert_eq!(cancelled.partial.get_pixel(0, py), expected);
+        }
+    }
+
+    #[test]
+    fn should_not_render_when_cancelled_up_front() {
+        let camera = Camera::new(4, 3, PI / 2.0);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let cancelled = camera
+            .render_parallel(2, |_| panic!("no rays expected"), |_| {}, &cancel)
+            .err()
+            .expect("render should be cancelled");
+
+        assert_eq!(cancelled.progress.rows_done, 0);
+    }
+
+    fn shaded_camera(sampling: Sampling) -> Camera {
+        let mut camera = Camera::new(12, 9, PI / 2.0);
+        camera.transform = Matrix::view_transform(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        camera.sampling = sampling;
+        camera
+    }
+
+    fn assert_same_pixels(image: &Canvas, full: &Canvas, region: Region) {
+        assert_eq!((image.width(), image.height), (region.width, region.height));
+        for py in 0..region.height {
+            for px in 0..region.width {
+                assert_eq!(
+                    image.get_pixel(px, py),
+                    full.get_pixel(region.x + px, region.y + py)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_render_cropped_region_like_the_full_image() {
+        let world = crate::world::tests::default_world();
+        let region = Region::new(3, 2, 5, 4);
+
+        for sampling in [
+            Sampling::Grid { n: 2 },
+            Sampling::Jittered { n: 2 },
+            Sampling::Adaptive {
+                n: 3,
+                threshold: 0.05,
+            },
+        ] {
+            let mut camera = shaded_camera(sampling);
+            let full = camera.render(|ray| world.color_at(ray));
+            camera.crop = Some(region);
+
+            let image = camera.render(|ray| world.color_at(ray));
+            assert_same_pixels(&image, &full, region);
+
+            let image = camera
+                .render_parallel(
+                    3,
+                    |ray| world.color_at(ray),
+                    |_| {},
+                    &CancellationToken::new(),
+                )
+                .unwrap();
+            assert_same_pixels(&image, &full, region);
+        }
+    }
+
+    #[test]
+    fn should_offset_rays_of_cropped_region() {
+        let mut camera = shaded_camera(Sampling::default());
+        camera.crop = Some(Region::new(4, 6, 2, 2));
+        let traced = Mutex::new(Vec::new());
+
+        camera.render(|ray| {
+            traced.lock().unwrap().push(ray.direction);
+            Color::new(0.0, 0.0, 0.0)
+        });
+
+        assert_eq!(
+            traced.into_inner().unwrap()[0],
+            camera.ray_for_pixel(4, 6).direction
+        );
+    }
+
+    #[test]
+    fn should_clip_crop_to_the_image() {
+        let mut camera = Camera::new(10, 8, PI / 2.0);
+        camera.crop = Some(Region::new(6, 4, 10, 10));
+
+        assert_eq!(camera.region(), Region::new(6, 4, 4, 4));
+        assert_eq!(camera.render(|_| Color::new(0.0, 0.0, 0.0)).width(), 4);
+    }
+
+    #[test]
+    fn should_stitch_tiles_into_the_full_image() {
+        let world = crate::world::tests::default_world();
+        let mut camera = shaded_camera(Sampling::Adaptive {
+            n: 2,
+            threshold: 0.05,
+        });
+        let full = camera.render(|ray| world.color_at(ray));
+
+        let tiles = Region::tiles(camera.hsize, camera.vsize, 5, 4)
+            .into_iter()
+            .map(|tile| {
+                camera.crop = Some(tile);
+                (tile, camera.render(|ray| world.color_at(ray)))
+            })
+            .collect::<Vec<(Region, Canvas)>>();
+        let image = Canvas::stitch(
+            12,
+            9,
+            full.color_space,
+            tiles.iter().map(|(region, canvas)| (*region, canvas)),
+        );
+
+        assert_same_pixels(&image, &full, Region::new(0, 0, 12, 9));
+    }
+}
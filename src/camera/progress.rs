@@ -0,0 +1,119 @@
+use crate::canvas::Canvas;
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How far a render has come, reported after every finished row.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    pub rows_done: usize,
+    /// Rows across all passes, adaptive sampling goes over the image twice.
+    pub rows_total: usize,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn fraction(&self) -> f64 {
+        if self.rows_total == 0 {
+            return 1.0;
+        }
+        self.rows_done as f64 / self.rows_total as f64
+    }
+
+    /// Estimates the time left from the average time per row so far.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.rows_done == 0 {
+            return None;
+        }
+        let remaining = (self.rows_total - self.rows_done) as f64;
+        Some(self.elapsed.mul_f64(remaining / self.rows_done as f64))
+    }
+}
+
+/// Shared flag that stops a render from another thread. Clones refer to
+/// the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Returned by a cancelled render. Rows that were finished are kept in
+/// `partial`, the others are black, or hold their first adaptive pass.
+pub struct Cancelled {
+    pub partial: Canvas,
+    pub progress: Progress,
+}
+
+impl fmt::Debug for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cancelled")
+            .field("progress", &self.progress)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "render cancelled after {} of {} rows",
+            self.progress.rows_done, self.progress.rows_total
+        )
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_estimate_remaining_time() {
+        let progress = Progress {
+            rows_done: 25,
+            rows_total: 100,
+            elapsed: Duration::from_secs(10),
+        };
+
+        assert_eq!(progress.fraction(), 0.25);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn should_not_estimate_before_the_first_row() {
+        let progress = Progress {
+            rows_done: 0,
+            rows_total: 100,
+            elapsed: Duration::from_millis(5),
+        };
+
+        assert_eq!(progress.eta(), None);
+    }
+
+    #[test]
+    fn should_share_cancellation_between_clones() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+
+        assert!(!clone.is_cancelled());
+        token.cancel();
+        assert!(clone.is_cancelled());
+    }
+}
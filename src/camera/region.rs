@@ -0,0 +1,157 @@
+/// A rectangle of pixels whose top left pixel is at `x`, `y`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, px: usize, py: usize) -> bool {
+        // subtracts instead of adding, regions may reach past `usize::MAX`
+        px.checked_sub(self.x).is_some_and(|dx| dx < self.width)
+            && py.checked_sub(self.y).is_some_and(|dy| dy < self.height)
+    }
+
+    /// The overlap of both regions, which is empty if they don't overlap.
+    pub fn intersect(&self, other: &Region) -> Region {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Region::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+
+    /// Grows the region by `margin` pixels on every side, without leaving
+    /// `bounds`.
+    pub fn grow(&self, margin: usize, bounds: &Region) -> Region {
+        let x = self.x.saturating_sub(margin);
+        let y = self.y.saturating_sub(margin);
+        let grown = Region::new(
+            x,
+            y,
+            self.right().saturating_add(margin) - x,
+            self.bottom().saturating_add(margin) - y,
+        );
+        grown.intersect(bounds)
+    }
+
+    /// The column after the region, at most `usize::MAX`.
+    fn right(&self) -> usize {
+        self.x.saturating_add(self.width)
+    }
+
+    /// The row below the region, at most `usize::MAX`.
+    fn bottom(&self) -> usize {
+        self.y.saturating_add(self.height)
+    }
+
+    /// Splits a `width` by `height` image into tiles of at most `tile_width`
+    /// by `tile_height` pixels, row by row.
+    pub fn tiles(
+        width: usize,
+        height: usize,
+        tile_width: usize,
+        tile_height: usize,
+    ) -> Vec<Region> {
+        let bounds = Region::new(0, 0, width, height);
+        (0..height)
+            .step_by(tile_height.max(1))
+            .flat_map(|y| {
+                (0..width)
+                    .step_by(tile_width.max(1))
+                    .map(move |x| Region::new(x, y, tile_width, tile_height).intersect(&bounds))
+            })
+            .collect()
+    }
+
+    /// Position of the pixel at `px`, `py` in a row major buffer holding
+    /// just this region.
+    pub fn index(&self, px: usize, py: usize) -> usize {
+        (px - self.x) + (py - self.y) * self.width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_intersect_regions() {
+        let a = Region::new(0, 0, 10, 10);
+
+        assert_eq!(
+            a.intersect(&Region::new(5, 8, 10, 10)),
+            Region::new(5, 8, 5, 2)
+        );
+        assert!(a.intersect(&Region::new(10, 0, 5, 5)).is_empty());
+    }
+
+    #[test]
+    fn should_grow_within_bounds() {
+        let bounds = Region::new(0, 0, 10, 10);
+
+        assert_eq!(
+            Region::new(0, 4, 3, 3).grow(1, &bounds),
+            Region::new(0, 3, 4, 5)
+        );
+        assert_eq!(
+            Region::new(8, 8, 2, 2).grow(2, &bounds),
+            Region::new(6, 6, 4, 4)
+        );
+    }
+
+    #[test]
+    fn should_split_image_into_tiles() {
+        let tiles = Region::tiles(5, 3, 2, 2);
+
+        assert_eq!(
+            tiles,
+            vec![
+                Region::new(0, 0, 2, 2),
+                Region::new(2, 0, 2, 2),
+                Region::new(4, 0, 1, 2),
+                Region::new(0, 2, 2, 1),
+                Region::new(2, 2, 2, 1),
+                Region::new(4, 2, 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_index_pixels_inside_region() {
+        let region = Region::new(3, 2, 4, 5);
+
+        assert!(region.contains(3, 2));
+        assert!(!region.contains(7, 2));
+        assert_eq!(region.index(3, 2), 0);
+        assert_eq!(region.index(5, 3), 6);
+    }
+
+    #[test]
+    fn should_handle_regions_reaching_past_the_largest_index() {
+        let region = Region::new(usize::MAX - 1, 2, 10, usize::MAX);
+        let bounds = Region::new(0, 0, usize::MAX, usize::MAX);
+
+        assert!(region.contains(usize::MAX - 1, usize::MAX - 1));
+        assert!(!region.contains(0, 2));
+        assert_eq!(
+            region.grow(1, &bounds),
+            Region::new(usize::MAX - 2, 1, 2, usize::MAX - 1)
+        );
+    }
+}
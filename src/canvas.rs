@@ -1,36 +1,111 @@
-use crate::color::Color;
+mod hdr;
+mod png;
+mod ppm;
+mod tone_map;
 
+use crate::camera::Region;
+use crate::color::{Color, ColorSpace};
+use crate::float::Float;
+
+use std::fmt;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Hdr,
+    Pfm,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Result<ImageFormat, ImageError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("ppm") => Ok(ImageFormat::Ppm),
+            Some("png") => Ok(ImageFormat::Png),
+            Some("hdr") => Ok(ImageFormat::Hdr),
+            Some("pfm") => Ok(ImageFormat::Pfm),
+            _ => Err(ImageError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "could not write image: {}", error),
+            ImageError::UnsupportedFormat(path) => {
+                write!(
+                    f,
+                    "no image encoder for {:?}, expected .ppm, .png, .hdr or .pfm",
+                    path
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(error) => Some(error),
+            ImageError::UnsupportedFormat(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
 
-pub struct Canvas {
+/// A grid of linear colors. Renders fill `f64` canvases, `f32` halves the
+/// memory of large framebuffers.
+pub struct Canvas<T = f64> {
     width: usize,
     pub height: usize,
-    pixels: Vec<Color>,
+    pixels: Vec<Color<T>>,
+    /// Transfer function every image writer applies to the stored linear
+    /// pixels. Float formats are normally kept linear.
+    pub color_space: ColorSpace,
 }
 
 impl Canvas {
     pub fn new(width: usize, height: usize) -> Canvas {
-        Canvas {
-            width,
-            height,
-            pixels: vec![Color::new(0.0, 0.0, 0.0); width * height],
-        }
+        Canvas::with_color(width, height, Color::new(0.0, 0.0, 0.0))
     }
+}
 
-    fn with_color(width: usize, height: usize, color: Color) -> Canvas {
+impl<T: Float> Canvas<T> {
+    /// A canvas filled with `color`, in the precision of `color`.
+    pub fn with_color(width: usize, height: usize, color: Color<T>) -> Canvas<T> {
         Canvas {
             width,
             height,
             pixels: vec![color; width * height],
+            color_space: ColorSpace::Linear,
         }
     }
 
-    pub fn get_pixel(&self, x: usize, y: usize) -> Color {
+    pub fn get_pixel(&self, x: usize, y: usize) -> Color<T> {
         self.pixels[self.get_index(x, y)]
     }
 
-    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color<T>) {
         let index = self.get_index(x, y);
         if index >= self.pixels.len() {
             return;
@@ -38,19 +113,80 @@ impl Canvas {
         self.pixels[index] = color;
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Converts every pixel to another precision.
+    pub fn cast<U: Float>(&self) -> Canvas<U> {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.iter().map(Color::cast).collect(),
+            color_space: self.color_space,
+        }
+    }
+
+    /// Copies `source` into the canvas with its top left pixel at `x`, `y`.
+    /// Pixels falling outside the canvas are dropped.
+    pub fn paste(&mut self, source: &Canvas<T>, x: usize, y: usize) {
+        for sy in 0..source.height.min(self.height.saturating_sub(y)) {
+            for sx in 0..source.width.min(self.width.saturating_sub(x)) {
+                self.set_pixel(x + sx, y + sy, source.get_pixel(sx, sy));
+            }
+        }
+    }
+
+    /// Assembles a `width` by `height` image from canvases rendered for
+    /// parts of it, like the tiles of `Region::tiles`. Pixels are stored
+    /// linear, so parts may come in any color space and the image is written
+    /// in `color_space`.
+    pub fn stitch<'a>(
+        width: usize,
+        height: usize,
+        color_space: ColorSpace,
+        parts: impl IntoIterator<Item = (Region, &'a Canvas<T>)>,
+    ) -> Canvas<T>
+    where
+        T: 'a,
+    {
+        let mut image = Canvas::with_color(width, height, Color::new(T::ZERO, T::ZERO, T::ZERO));
+        image.color_space = color_space;
+        for (region, canvas) in parts {
+            image.paste(canvas, region.x, region.y);
+        }
+        image
+    }
+
     pub fn to_ppm_header(&self) -> String {
         format!("P3\n{} {}\n255\n", self.width, self.height)
     }
 
-    pub fn write_to_file(&self) -> std::io::Result<()> {
-        let mut buffer = File::create("foo.ppm")?;
-        write!(buffer, "{}", self.to_ppm())
+    pub fn write_to(&self, writer: &mut impl Write, format: ImageFormat) -> Result<(), ImageError> {
+        match format {
+            ImageFormat::Ppm => writer.write_all(self.to_ppm().as_bytes())?,
+            ImageFormat::Png => self.write_png(writer)?,
+            ImageFormat::Hdr => self.write_hdr(writer)?,
+            ImageFormat::Pfm => self.write_pfm(writer)?,
+        }
+        Ok(())
+    }
+
+    /// Writes the canvas to `path`, picking the encoder from the file extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ImageError> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path)?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer, format)?;
+        writer.flush()?;
+        Ok(())
     }
 
     pub fn to_ppm_body(&self) -> String {
         let mut data = self
             .pixels
             .iter()
+            .map(|pixel| self.color_space.encode(*pixel))
             .map(Color::to_true_color)
             .flat_map(|pixel| pixel.iter().map(u8::to_string).collect::<Vec<String>>())
             .collect::<Vec<String>>();
@@ -101,7 +237,7 @@ impl Canvas {
 fn should_initalize_a_black_canvas() {
     let canvas = Canvas::new(3, 3);
     let is_all_black = canvas.pixels.iter().all(|x| x.is_black());
-    assert_eq!(is_all_black, true);
+    assert!(is_all_black);
 }
 
 #[test]
@@ -138,7 +274,7 @@ fn should_create_ppm_pixel_data() {
 5 3
 255
 255 0 0 0 0 0 0 0 0 0 0 0 0 0 0
-0 0 0 0 0 0 0 127 0 0 0 0 0 0 0
+0 0 0 0 0 0 0 128 0 0 0 0 0 0 0
 0 0 0 0 0 0 0 0 0 0 0 0 0 0 255
 ";
     let actual = canvas.to_ppm();
@@ -159,3 +295,141 @@ fn should_split_long_lines() {
 
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn should_write_ppm_to_a_writer() {
+    let canvas = Canvas::with_color(2, 1, Color::new(1.0, 0.0, 0.0));
+    let mut buffer = Vec::new();
+
+    canvas.write_to(&mut buffer, ImageFormat::Ppm).unwrap();
+
+    assert_eq!(buffer, canvas.to_ppm().into_bytes());
+}
+
+#[test]
+fn should_pick_image_format_from_extension() {
+    assert_eq!(
+        ImageFormat::from_path(Path::new("render.ppm")).unwrap(),
+        ImageFormat::Ppm
+    );
+    assert_eq!(
+        ImageFormat::from_path(Path::new("RENDER.PPM")).unwrap(),
+        ImageFormat::Ppm
+    );
+    assert_eq!(
+        ImageFormat::from_path(Path::new("render.png")).unwrap(),
+        ImageFormat::Png
+    );
+    assert_eq!(
+        ImageFormat::from_path(Path::new("render.hdr")).unwrap(),
+        ImageFormat::Hdr
+    );
+    assert_eq!(
+        ImageFormat::from_path(Path::new("render.pfm")).unwrap(),
+        ImageFormat::Pfm
+    );
+    assert!(matches!(
+        ImageFormat::from_path(Path::new("render.bmp")),
+        Err(ImageError::UnsupportedFormat(_))
+    ));
+    assert!(matches!(
+        ImageFormat::from_path(Path::new("render")),
+        Err(ImageError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn should_save_canvas_to_a_path() {
+    let mut canvas = Canvas::new(3, 2);
+    canvas.set_pixel(1, 1, Color::new(0.0, 1.0, 0.0));
+    let path = std::env::temp_dir().join("should_save_canvas_to_a_path.ppm");
+
+    canvas.save(&path).unwrap();
+    let saved = Canvas::from_ppm(&std::fs::read(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(saved.get_pixel(1, 1), Color::new(0.0, 1.0, 0.0));
+    assert_eq!(saved.get_pixel(0, 0), Color::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn should_encode_ppm_pixel_data_in_srgb() {
+    let mut canvas = Canvas::with_color(2, 1, Color::new(0.5, 0.0, 1.0));
+    canvas.color_space = ColorSpace::Srgb;
+
+    let expected = "188 0 255 188 0 255\n";
+    let actual = canvas.to_ppm_body();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_paste_canvas_clipping_at_the_edges() {
+    let mut canvas = Canvas::new(4, 3);
+    let red = Color::new(1.0, 0.0, 0.0);
+    let patch = Canvas::with_color(2, 2, red);
+
+    canvas.paste(&patch, 3, 1);
+
+    assert_eq!(canvas.get_pixel(3, 1), red);
+    assert_eq!(canvas.get_pixel(3, 2), red);
+    assert_eq!(canvas.get_pixel(2, 1), Color::new(0.0, 0.0, 0.0));
+    assert_eq!(canvas.get_pixel(0, 2), Color::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn should_stitch_parts_keeping_their_color_space() {
+    let mut part = Canvas::with_color(2, 1, Color::new(1.0, 0.0, 0.0));
+    part.color_space = ColorSpace::Srgb;
+
+    let image = Canvas::stitch(
+        2,
+        2,
+        ColorSpace::Srgb,
+        [
+            (Region::new(0, 0, 2, 1), &part),
+            (Region::new(0, 1, 2, 1), &part),
+        ],
+    );
+
+    assert_eq!(image.color_space, ColorSpace::Srgb);
+    assert_eq!(image.get_pixel(1, 1), Color::new(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn should_stitch_parts_of_different_color_spaces() {
+    let linear = Canvas::with_color(2, 1, Color::new(0.5, 0.5, 0.5));
+    let mut srgb = Canvas::with_color(2, 1, Color::new(0.5, 0.5, 0.5));
+    srgb.color_space = ColorSpace::Srgb;
+
+    let image = Canvas::stitch(
+        2,
+        2,
+        ColorSpace::Srgb,
+        [
+            (Region::new(0, 0, 2, 1), &linear),
+            (Region::new(0, 1, 2, 1), &srgb),
+        ],
+    );
+
+    assert_eq!(image.color_space, ColorSpace::Srgb);
+    assert_eq!(image.get_pixel(0, 0), Color::new(0.5, 0.5, 0.5));
+    assert_eq!(image.get_pixel(1, 1), Color::new(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn should_store_pixels_in_single_precision() {
+    let mut canvas = Canvas::with_color(2, 1, Color::new(0.0_f32, 0.0, 0.0));
+    canvas.color_space = ColorSpace::Srgb;
+    canvas.set_pixel(1, 0, Color::new(0.5, 0.25, 1.0));
+
+    let double = canvas.cast::<f64>();
+
+    assert_eq!(double.get_pixel(1, 0), Color::new(0.5, 0.25, 1.0));
+    assert_eq!(double.color_space, ColorSpace::Srgb);
+    assert_eq!(canvas.to_ppm(), double.to_ppm());
+    let (mut single_png, mut double_png) = (Vec::new(), Vec::new());
+    canvas.write_png(&mut single_png).unwrap();
+    double.write_png(&mut double_png).unwrap();
+    assert_eq!(single_png, double_png);
+}
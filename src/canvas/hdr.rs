@@ -0,0 +1,257 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::float::Float;
+
+use std::io::{self, Write};
+
+const MIN_RLE_WIDTH: usize = 8;
+const MAX_RLE_WIDTH: usize = 0x7fff;
+const MIN_RUN: usize = 4;
+const MAX_RUN: usize = 127;
+const MAX_LITERAL: usize = 128;
+
+/// Splits `value` into a mantissa in 0.5..1 and a power of two exponent.
+fn frexp(value: f64) -> (f64, i32) {
+    let mut exponent = value.log2().floor() as i32 + 1;
+    let mut mantissa = value / 2.0_f64.powi(exponent);
+    if mantissa >= 1.0 {
+        mantissa /= 2.0;
+        exponent += 1;
+    } else if mantissa < 0.5 {
+        mantissa *= 2.0;
+        exponent -= 1;
+    }
+    (mantissa, exponent)
+}
+
+/// Packs a color into Radiance's shared exponent format. Negative and NaN
+/// channels are stored as zero since RGBE has no sign, infinite ones
+/// saturate like any value too bright for the exponent.
+fn to_rgbe(color: Color) -> [u8; 4] {
+    let channel = |value: f64| {
+        if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, f64::MAX)
+        }
+    };
+    let (r, g, b) = (channel(color.r), channel(color.g), channel(color.b));
+    let max = r.max(g).max(b);
+    if max < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    let (mantissa, exponent) = frexp(max);
+    if exponent > 127 {
+        return [255, 255, 255, 255];
+    }
+    let scale = mantissa * 256.0 / max;
+    [
+        (r * scale) as u8,
+        (g * scale) as u8,
+        (b * scale) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+fn write_rle_component(writer: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    let mut position = 0;
+    while position < data.len() {
+        let mut run_start = position;
+        let mut run_length = 0;
+        while run_start < data.len() {
+            run_length = data[run_start..]
+                .iter()
+                .take(MAX_RUN)
+                .take_while(|byte| **byte == data[run_start])
+                .count();
+            if run_length >= MIN_RUN {
+                break;
+            }
+            run_start += run_length;
+        }
+        if run_length < MIN_RUN {
+            run_start = data.len();
+        }
+
+        for literal in data[position..run_start].chunks(MAX_LITERAL) {
+            writer.write_all(&[literal.len() as u8])?;
+            writer.write_all(literal)?;
+        }
+        if run_start < data.len() {
+            writer.write_all(&[128 + run_length as u8, data[run_start]])?;
+        }
+        position = run_start + run_length;
+    }
+    Ok(())
+}
+
+impl<T: Float> Canvas<T> {
+    /// Encodes the canvas as a Radiance RGBE image, keeping every value
+    /// above 1.0 instead of clamping it away.
+    pub fn write_hdr(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(
+            writer,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )?;
+
+        let use_rle = (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&self.width);
+        // a canvas without columns has no pixels, any chunk size yields no rows
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let rgbe = row
+                .iter()
+                .map(|pixel| to_rgbe(self.color_space.encode(*pixel).cast()))
+                .collect::<Vec<[u8; 4]>>();
+            if !use_rle {
+                for pixel in rgbe {
+                    writer.write_all(&pixel)?;
+                }
+                continue;
+            }
+
+            writer.write_all(&[2, 2, (self.width >> 8) as u8, self.width as u8])?;
+            for component in 0..4 {
+                let data = rgbe
+                    .iter()
+                    .map(|pixel| pixel[component])
+                    .collect::<Vec<u8>>();
+                write_rle_component(writer, &data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the canvas as a little endian Portable Float Map. Channels are
+    /// stored as `f32`, so values outside 0..1 survive unchanged.
+    pub fn write_pfm(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(writer, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+
+        // PFM scanlines go from the bottom of the image to the top
+        for row in self.pixels.chunks(self.width.max(1)).rev() {
+            for pixel in row {
+                let pixel = self.color_space.encode(*pixel);
+                for channel in [pixel.r, pixel.g, pixel.b] {
+                    writer.write_all(&(channel.to_f64() as f32).to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn read_rle_component(data: &[u8], width: usize) -> (Vec<u8>, usize) {
+    let mut component = Vec::new();
+    let mut position = 0;
+    while component.len() < width {
+        let count = data[position] as usize;
+        if count > 128 {
+            component.extend(std::iter::repeat_n(data[position + 1], count - 128));
+            position += 2;
+        } else {
+            component.extend_from_slice(&data[position + 1..position + 1 + count]);
+            position += 1 + count;
+        }
+    }
+    (component, position)
+}
+
+#[test]
+fn should_convert_color_to_rgbe() {
+    assert_eq!(to_rgbe(Color::new(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+    assert_eq!(to_rgbe(Color::new(1.0, 1.0, 1.0)), [128, 128, 128, 129]);
+    assert_eq!(to_rgbe(Color::new(0.5, 0.25, 0.0)), [128, 64, 0, 128]);
+    assert_eq!(to_rgbe(Color::new(12.0, 3.0, -1.0)), [192, 48, 0, 132]);
+}
+
+#[test]
+fn should_convert_non_finite_channels_to_rgbe() {
+    assert_eq!(to_rgbe(Color::new(f64::NAN, 0.5, 0.0)), [0, 128, 0, 128]);
+    assert_eq!(
+        to_rgbe(Color::new(f64::INFINITY, 0.0, 0.0)),
+        [255, 255, 255, 255]
+    );
+    assert_eq!(
+        to_rgbe(Color::new(f64::NEG_INFINITY, 0.0, 0.0)),
+        [0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn should_split_float_into_mantissa_and_exponent() {
+    assert_eq!(frexp(1.0), (0.5, 1));
+    assert_eq!(frexp(0.75), (0.75, 0));
+    assert_eq!(frexp(12.0), (0.75, 4));
+}
+
+#[test]
+fn should_write_hdr_header() {
+    let canvas = Canvas::new(3, 2);
+    let mut hdr = Vec::new();
+
+    canvas.write_hdr(&mut hdr).unwrap();
+
+    let header = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 3\n";
+    assert!(hdr.starts_with(header.as_bytes()));
+    assert_eq!(hdr.len(), header.len() + 3 * 2 * 4);
+}
+
+#[test]
+fn should_write_run_length_encoded_hdr_scanlines() {
+    let mut canvas = Canvas::with_color(10, 1, Color::new(4.0, 2.0, 1.0));
+    canvas.set_pixel(0, 0, Color::new(0.5, 0.0, 0.0));
+    canvas.set_pixel(9, 0, Color::new(0.0, 0.0, 0.25));
+    let mut hdr = Vec::new();
+
+    canvas.write_hdr(&mut hdr).unwrap();
+
+    let header = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 10\n".len();
+    let mut scanline = &hdr[header..];
+    assert_eq!(&scanline[..4], &[2, 2, 0, 10]);
+    scanline = &scanline[4..];
+
+    let mut pixels = [[0; 4]; 10];
+    for component in 0..4 {
+        let (data, read) = read_rle_component(scanline, 10);
+        for (pixel, byte) in pixels.iter_mut().zip(data) {
+            pixel[component] = byte;
+        }
+        scanline = &scanline[read..];
+    }
+
+    assert!(scanline.is_empty());
+    assert_eq!(pixels[0], to_rgbe(Color::new(0.5, 0.0, 0.0)));
+    assert_eq!(pixels[5], to_rgbe(Color::new(4.0, 2.0, 1.0)));
+    assert_eq!(pixels[9], to_rgbe(Color::new(0.0, 0.0, 0.25)));
+}
+
+#[test]
+fn should_write_pfm_bottom_to_top() {
+    let mut canvas = Canvas::new(1, 2);
+    canvas.set_pixel(0, 0, Color::new(5.5, 0.0, -1.0));
+    let mut pfm = Vec::new();
+
+    canvas.write_pfm(&mut pfm).unwrap();
+
+    let header = b"PF\n1 2\n-1.0\n";
+    assert!(pfm.starts_with(header));
+
+    let floats = pfm[header.len()..]
+        .chunks(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect::<Vec<f32>>();
+    assert_eq!(floats, vec![0.0, 0.0, 0.0, 5.5, 0.0, -1.0]);
+}
+
+#[test]
+fn should_write_canvas_without_columns() {
+    let canvas = Canvas::new(0, 2);
+    let (mut hdr, mut pfm) = (Vec::new(), Vec::new());
+
+    canvas.write_hdr(&mut hdr).unwrap();
+    canvas.write_pfm(&mut pfm).unwrap();
+
+    assert_eq!(hdr, b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 0\n");
+    assert_eq!(pfm, b"PF\n0 2\n-1.0\n");
+}
@@ -0,0 +1,267 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::float::Float;
+
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK: usize = 65535;
+/// PNG stores chunk lengths and image dimensions as 4 byte integers that may
+/// not exceed 2^31 - 1.
+const MAX_PNG_INTEGER: u32 = (1 << 31) - 1;
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            c = if c & 1 == 1 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            bit += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+fn crc32(chunks: &[&[u8]]) -> u32 {
+    let mut crc = 0xffffffff;
+    for chunk in chunks {
+        for byte in chunk.iter() {
+            crc = CRC32_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+    }
+    crc ^ 0xffffffff
+}
+
+/// Converts `value` to a PNG integer, failing with `InvalidInput` if it is
+/// too large for one.
+fn png_integer(value: usize, name: &str) -> io::Result<u32> {
+    u32::try_from(value)
+        .ok()
+        .filter(|value| *value <= MAX_PNG_INTEGER)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} {} is too large for a PNG", name, value),
+            )
+        })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (mut a, mut b) = (1, 0);
+    // 5552 is the largest n for which the sums cannot overflow a u32
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+/// Wraps `data` in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut stream = Vec::with_capacity(data.len() + blocks * 5 + 6);
+
+    // CMF: deflate with a 32K window, FLG: no dictionary, fastest compression
+    stream.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let is_final = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        stream.push(is_final as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(chunk);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let len = png_integer(data.len(), "chunk length")?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc32(&[kind, data]).to_be_bytes())
+}
+
+impl<T: Float> Canvas<T> {
+    /// Encodes the canvas as an 8-bit RGB PNG. PNG has no empty images, a
+    /// canvas without pixels or one too large for PNG fails with
+    /// `InvalidInput`.
+    pub fn write_png(&self, writer: &mut impl Write) -> io::Result<()> {
+        let width = png_integer(self.width, "width")?;
+        let height = png_integer(self.height, "height")?;
+        if self.width == 0 || self.height == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot encode a {}x{} PNG", self.width, self.height),
+            ));
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // bit depth 8, color type RGB, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut scanlines = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width) {
+            // every scanline starts with its filter type, 0 means unfiltered
+            scanlines.push(0);
+            scanlines.extend(
+                row.iter()
+                    .map(|pixel| self.color_space.encode(*pixel))
+                    .flat_map(Color::to_true_color),
+            );
+        }
+
+        writer.write_all(&SIGNATURE)?;
+        write_chunk(writer, b"IHDR", &header)?;
+        write_chunk(writer, b"IDAT", &zlib_stored(&scanlines))?;
+        write_chunk(writer, b"IEND", &[])
+    }
+}
+
+#[cfg(test)]
+fn read_u32(data: &[u8]) -> u32 {
+    u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+}
+
+#[cfg(test)]
+fn inflate_stored(stream: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut position = 2;
+    loop {
+        let is_final = stream[position] & 1 == 1;
+        let len = u16::from_le_bytes([stream[position + 1], stream[position + 2]]) as usize;
+        position += 5;
+        data.extend_from_slice(&stream[position..position + len]);
+        position += len;
+        if is_final {
+            break;
+        }
+    }
+    assert_eq!(read_u32(&stream[position..]), adler32(&data));
+    data
+}
+
+#[test]
+fn should_calculate_crc32() {
+    assert_eq!(crc32(&[b"123456789"]), 0xcbf43926);
+    assert_eq!(crc32(&[b"1234", b"56789"]), 0xcbf43926);
+    assert_eq!(crc32(&[b"IEND"]), 0xae426082);
+}
+
+#[test]
+fn should_calculate_adler32() {
+    assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    assert_eq!(adler32(b""), 1);
+}
+
+#[test]
+fn should_split_zlib_stream_into_stored_blocks() {
+    let data = (0..150_000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+
+    let stream = zlib_stored(&data);
+
+    assert_eq!(&stream[..2], &[0x78, 0x01]);
+    assert_eq!(stream.len(), 2 + 3 * 5 + data.len() + 4);
+    assert_eq!(inflate_stored(&stream), data);
+}
+
+#[test]
+fn should_write_png_header_and_chunks() {
+    let canvas = Canvas::new(5, 3);
+    let mut png = Vec::new();
+
+    canvas.write_png(&mut png).unwrap();
+
+    assert_eq!(&png[..8], &SIGNATURE);
+    assert_eq!(read_u32(&png[8..]), 13);
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(read_u32(&png[16..]), 5);
+    assert_eq!(read_u32(&png[20..]), 3);
+    assert_eq!(&png[24..29], &[8, 2, 0, 0, 0]);
+    assert_eq!(read_u32(&png[29..]), crc32(&[&png[12..29]]));
+    assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+}
+
+#[test]
+fn should_write_png_pixel_data() {
+    let mut canvas = Canvas::new(2, 2);
+    canvas.set_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+    canvas.set_pixel(1, 0, Color::new(0.0, 1.0, 0.0));
+    canvas.set_pixel(0, 1, Color::new(0.0, 0.0, 1.0));
+    canvas.set_pixel(1, 1, Color::new(1.0, 1.0, 1.0));
+    let mut png = Vec::new();
+
+    canvas.write_png(&mut png).unwrap();
+
+    let idat = &png[33..];
+    let len = read_u32(idat) as usize;
+    assert_eq!(&idat[4..8], b"IDAT");
+    assert_eq!(read_u32(&idat[8 + len..]), crc32(&[&idat[4..8 + len]]));
+
+    let expected = vec![
+        0, 255, 0, 0, 0, 255, 0, //
+        0, 0, 0, 255, 255, 255, 255,
+    ];
+    assert_eq!(inflate_stored(&idat[8..8 + len]), expected);
+}
+
+#[test]
+fn should_reject_empty_canvas() {
+    for canvas in [Canvas::new(0, 0), Canvas::new(0, 3), Canvas::new(3, 0)] {
+        let mut png = Vec::new();
+
+        let error = canvas.write_png(&mut png).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(png.is_empty());
+    }
+}
+
+#[test]
+fn should_reject_canvas_too_large_for_png() {
+    let canvas = Canvas::new(1 << 31, 0);
+    let mut png = Vec::new();
+
+    let error = canvas.write_png(&mut png).unwrap_err();
+
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("width"));
+    assert!(png.is_empty());
+}
+
+#[test]
+fn should_limit_png_integers() {
+    assert_eq!(png_integer(0, "width").unwrap(), 0);
+    assert_eq!(
+        png_integer((1 << 31) - 1, "width").unwrap(),
+        MAX_PNG_INTEGER
+    );
+    assert_eq!(
+        png_integer(1 << 31, "width").unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+    assert!(png_integer(usize::MAX, "chunk length").is_err());
+}
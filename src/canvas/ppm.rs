@@ -0,0 +1,414 @@
+#[cfg(test)]
+use crate::approx::ApproxEq;
+use crate::canvas::Canvas;
+use crate::color::{Color, ColorSpace};
+
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum PpmError {
+    UnexpectedEof(&'static str),
+    UnknownMagicNumber(String),
+    InvalidNumber { field: &'static str, token: String },
+    InvalidMaxval(u32),
+    InvalidDimensions { width: usize, height: usize },
+    SampleOutOfRange { sample: u32, maxval: u32 },
+    MissingWhitespace,
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpmError::UnexpectedEof(expected) => {
+                write!(f, "unexpected end of file while reading {}", expected)
+            }
+            PpmError::UnknownMagicNumber(magic) => {
+                write!(f, "unknown magic number {:?}, expected P3 or P6", magic)
+            }
+            PpmError::InvalidNumber { field, token } => {
+                write!(f, "invalid {}: {:?} is not a number", field, token)
+            }
+            PpmError::InvalidMaxval(maxval) => {
+                write!(f, "invalid maxval {}, must be between 1 and 65535", maxval)
+            }
+            PpmError::InvalidDimensions { width, height } => {
+                write!(f, "invalid dimensions {}x{}", width, height)
+            }
+            PpmError::SampleOutOfRange { sample, maxval } => {
+                write!(f, "sample {} is larger than maxval {}", sample, maxval)
+            }
+            PpmError::MissingWhitespace => {
+                write!(f, "expected a single whitespace character before raster")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte == b'#' {
+                while let Some(&byte) = self.data.get(self.position) {
+                    if byte == b'\n' || byte == b'\r' {
+                        break;
+                    }
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self, field: &'static str) -> Result<&'a str, PpmError> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte.is_ascii_whitespace() || byte == b'#' {
+                break;
+            }
+            self.position += 1;
+        }
+
+        if start == self.position {
+            return Err(PpmError::UnexpectedEof(field));
+        }
+
+        std::str::from_utf8(&self.data[start..self.position]).map_err(|_| PpmError::InvalidNumber {
+            field,
+            token: String::from_utf8_lossy(&self.data[start..self.position]).into_owned(),
+        })
+    }
+
+    fn number(&mut self, field: &'static str) -> Result<u32, PpmError> {
+        let token = self.token(field)?;
+        token.parse().map_err(|_| PpmError::InvalidNumber {
+            field,
+            token: token.to_string(),
+        })
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    fn byte(&mut self, field: &'static str) -> Result<u8, PpmError> {
+        let byte = *self
+            .data
+            .get(self.position)
+            .ok_or(PpmError::UnexpectedEof(field))?;
+        self.position += 1;
+        Ok(byte)
+    }
+}
+
+impl Canvas {
+    /// Parses a plain (P3) or raw (P6) PPM image. Samples are divided by the
+    /// maxval of the file, so every channel ends up in the 0..1 range, and
+    /// are kept as they are. Use `from_ppm_in` for images written from a
+    /// canvas with an sRGB color space.
+    pub fn from_ppm(data: &[u8]) -> Result<Canvas, PpmError> {
+        Canvas::from_ppm_in(data, ColorSpace::Linear)
+    }
+
+    /// Parses a PPM image whose samples were encoded with `color_space` and
+    /// decodes them back to linear values. The canvas keeps `color_space`,
+    /// so writing it again encodes the pixels the same way.
+    pub fn from_ppm_in(data: &[u8], color_space: ColorSpace) -> Result<Canvas, PpmError> {
+        let mut reader = Reader::new(data);
+
+        let magic = reader.token("magic number")?;
+        let binary = match magic {
+            "P3" => false,
+            "P6" => true,
+            _ => return Err(PpmError::UnknownMagicNumber(magic.to_string())),
+        };
+
+        let width = reader.number("width")? as usize;
+        let height = reader.number("height")? as usize;
+        if width == 0 || height == 0 {
+            return Err(PpmError::InvalidDimensions { width, height });
+        }
+
+        let maxval = reader.number("maxval")?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(PpmError::InvalidMaxval(maxval));
+        }
+
+        if binary && !reader.byte("raster")?.is_ascii_whitespace() {
+            return Err(PpmError::MissingWhitespace);
+        }
+
+        // checked before allocating, a header can claim far more pixels
+        // than the file holds
+        let sample_bytes = match (binary, maxval) {
+            (true, 256..) => 2,
+            // plain samples take at least one digit each
+            _ => 1,
+        };
+        let raster_size = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3 * sample_bytes))
+            .ok_or(PpmError::InvalidDimensions { width, height })?;
+        if reader.remaining() < raster_size {
+            return Err(PpmError::UnexpectedEof("sample"));
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        let sample = |reader: &mut Reader| -> Result<f64, PpmError> {
+            let value = match (binary, maxval) {
+                (false, _) => reader.number("sample")?,
+                (true, 1..=255) => reader.byte("sample")? as u32,
+                (true, _) => (reader.byte("sample")? as u32) << 8 | reader.byte("sample")? as u32,
+            };
+            if value > maxval {
+                return Err(PpmError::SampleOutOfRange {
+                    sample: value,
+                    maxval,
+                });
+            }
+            Ok(value as f64 / maxval as f64)
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let r = sample(&mut reader)?;
+                let g = sample(&mut reader)?;
+                let b = sample(&mut reader)?;
+                canvas.set_pixel(x, y, color_space.decode(Color::new(r, g, b)));
+            }
+        }
+
+        canvas.color_space = color_space;
+        Ok(canvas)
+    }
+}
+
+#[test]
+fn should_fail_on_unknown_magic_number() {
+    let ppm = "P32\n1 1\n255\n0 0 0\n";
+
+    let expected = Err(PpmError::UnknownMagicNumber("P32".to_string()));
+    let actual = Canvas::from_ppm(ppm.as_bytes()).map(|_| ());
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_read_dimensions_from_ppm() {
+    let ppm = "P3\n10 2\n255\n".to_string() + &"0 0 0\n".repeat(20);
+
+    let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+
+    assert_eq!(canvas.width, 10);
+    assert_eq!(canvas.height, 2);
+}
+
+#[test]
+fn should_read_pixel_data_from_ppm() {
+    let ppm = r"P3
+4 3
+255
+255 127 0  0 127 255  127 255 0  255 255 255
+0 0 0  255 0 0  0 255 0  0 0 255
+255 255 0  0 255 255  255 0 255  127 127 127
+";
+
+    let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+
+    assert_eq!(canvas.get_pixel(0, 0), Color::new(1.0, 0.49804, 0.0));
+    assert_eq!(canvas.get_pixel(1, 0), Color::new(0.0, 0.49804, 1.0));
+    assert_eq!(canvas.get_pixel(2, 0), Color::new(0.49804, 1.0, 0.0));
+    assert_eq!(canvas.get_pixel(3, 0), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(canvas.get_pixel(0, 1), Color::new(0.0, 0.0, 0.0));
+    assert_eq!(canvas.get_pixel(1, 1), Color::new(1.0, 0.0, 0.0));
+    assert_eq!(canvas.get_pixel(2, 1), Color::new(0.0, 1.0, 0.0));
+    assert_eq!(canvas.get_pixel(3, 1), Color::new(0.0, 0.0, 1.0));
+    assert_eq!(canvas.get_pixel(0, 2), Color::new(1.0, 1.0, 0.0));
+    assert_eq!(canvas.get_pixel(1, 2), Color::new(0.0, 1.0, 1.0));
+    assert_eq!(canvas.get_pixel(2, 2), Color::new(1.0, 0.0, 1.0));
+    assert_eq!(
+        canvas.get_pixel(3, 2),
+        Color::new(0.49804, 0.49804, 0.49804)
+    );
+}
+
+#[test]
+fn should_decode_srgb_ppm_to_linear_colors() {
+    let ppm = "P3\n2 1\n255\n188 0 255  128 128 128\n";
+
+    let canvas = Canvas::from_ppm_in(ppm.as_bytes(), ColorSpace::Srgb).unwrap();
+
+    assert_eq!(canvas.color_space, ColorSpace::Srgb);
+    assert_eq!(
+        canvas.get_pixel(0, 0),
+        Color::new(188.0 / 255.0, 0.0, 1.0).to_linear()
+    );
+    assert_eq!(
+        canvas.get_pixel(1, 0),
+        Color::new(0.21586, 0.21586, 0.21586)
+    );
+}
+
+#[test]
+fn should_round_trip_srgb_canvas_through_ppm() {
+    let mut canvas = Canvas::new(2, 1);
+    canvas.color_space = ColorSpace::Srgb;
+    canvas.set_pixel(0, 0, Color::new(0.5, 0.2, 0.0));
+    canvas.set_pixel(1, 0, Color::new(1.0, 0.01, 0.8));
+
+    let ppm = canvas.to_ppm();
+    let linear = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+    let decoded = Canvas::from_ppm_in(ppm.as_bytes(), ColorSpace::Srgb).unwrap();
+
+    assert!(linear
+        .get_pixel(0, 0)
+        .abs_diff_eq(&Color::new(0.5, 0.2, 0.0).to_srgb(), 0.005));
+    assert!(decoded
+        .get_pixel(0, 0)
+        .abs_diff_eq(&Color::new(0.5, 0.2, 0.0), 0.005));
+    assert!(decoded
+        .get_pixel(1, 0)
+        .abs_diff_eq(&Color::new(1.0, 0.01, 0.8), 0.005));
+    assert_eq!(decoded.to_ppm(), ppm);
+}
+
+#[test]
+fn should_ignore_comments_in_ppm() {
+    let ppm = r"P3
+# this is a comment
+2 1
+# this, too
+255
+# another comment
+255 255 255
+# oh, no, comments in the pixel data!
+255 0 255
+";
+
+    let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+
+    assert_eq!(canvas.get_pixel(0, 0), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(canvas.get_pixel(1, 0), Color::new(1.0, 0.0, 1.0));
+}
+
+#[test]
+fn should_allow_rgb_triple_to_span_lines() {
+    let ppm = r"P3
+1 1
+255
+51
+153
+
+204
+";
+
+    let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+
+    assert_eq!(canvas.get_pixel(0, 0), Color::new(0.2, 0.6, 0.8));
+}
+
+#[test]
+fn should_scale_samples_by_maxval() {
+    let ppm = "P3\n2 2\n100\n100 100 100  50 50 50\n75 50 25  0 0 0\n";
+
+    let canvas = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+
+    assert_eq!(canvas.get_pixel(0, 1), Color::new(0.75, 0.5, 0.25));
+}
+
+#[test]
+fn should_read_binary_ppm() {
+    let mut ppm = b"P6 # binary\n2 1\n255\n".to_vec();
+    ppm.extend_from_slice(&[255, 0, 51, 0, 255, 10]);
+
+    let canvas = Canvas::from_ppm(&ppm).unwrap();
+
+    assert_eq!(canvas.get_pixel(0, 0), Color::new(1.0, 0.0, 0.2));
+    assert_eq!(canvas.get_pixel(1, 0), Color::new(0.0, 1.0, 10.0 / 255.0));
+}
+
+#[test]
+fn should_read_binary_ppm_with_two_byte_samples() {
+    let mut ppm = b"P6\n1 1\n65535\n".to_vec();
+    ppm.extend_from_slice(&[0xff, 0xff, 0x80, 0x00, 0x00, 0x00]);
+
+    let canvas = Canvas::from_ppm(&ppm).unwrap();
+
+    assert_eq!(
+        canvas.get_pixel(0, 0),
+        Color::new(1.0, 32768.0 / 65535.0, 0.0)
+    );
+}
+
+#[test]
+fn should_fail_on_truncated_pixel_data() {
+    let ppm = "P3\n2 1\n255\n255 255 255 0\n";
+
+    let expected = Err(PpmError::UnexpectedEof("sample"));
+    let actual = Canvas::from_ppm(ppm.as_bytes()).map(|_| ());
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_fail_on_sample_larger_than_maxval() {
+    let ppm = "P3\n1 1\n15\n16 0 0\n";
+
+    let expected = Err(PpmError::SampleOutOfRange {
+        sample: 16,
+        maxval: 15,
+    });
+    let actual = Canvas::from_ppm(ppm.as_bytes()).map(|_| ());
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_fail_on_invalid_header_number() {
+    let ppm = "P3\nten 2\n255\n";
+
+    let expected = Err(PpmError::InvalidNumber {
+        field: "width",
+        token: "ten".to_string(),
+    });
+    let actual = Canvas::from_ppm(ppm.as_bytes()).map(|_| ());
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_fail_on_dimensions_larger_than_the_data() {
+    let ppm = "P6\n1000 1000\n255\n\x7f\x7f\x7f";
+
+    let expected = Err(PpmError::UnexpectedEof("sample"));
+    let actual = Canvas::from_ppm(ppm.as_bytes()).map(|_| ());
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_fail_on_overflowing_dimensions() {
+    let ppm = "P6\n4294967295 4294967295\n65535\n";
+
+    let expected = Err(PpmError::InvalidDimensions {
+        width: 4294967295,
+        height: 4294967295,
+    });
+    let actual = Canvas::from_ppm(ppm.as_bytes()).map(|_| ());
+
+    assert_eq!(expected, actual);
+}
@@ -0,0 +1,146 @@
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::float::Float;
+
+/// Operators that squeeze unbounded scene colors into the 0..1 display range.
+/// `exposure` is given in stops, every stop doubles the brightness.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ToneMap {
+    /// Scales by the exposure and leaves clamping to `to_true_color`.
+    Linear { exposure: f64 },
+    /// Extended Reinhard on luminance, `white` is the smallest luminance
+    /// mapped to pure white.
+    Reinhard { exposure: f64, white: f64 },
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve.
+    Aces { exposure: f64 },
+}
+
+impl ToneMap {
+    pub fn apply(&self, color: Color) -> Color {
+        match *self {
+            ToneMap::Linear { exposure } => color * exposure.exp2(),
+            ToneMap::Reinhard { exposure, white } => {
+                let color = color * exposure.exp2();
+                let luminance = color.luminance();
+                if luminance <= 0.0 {
+                    return Color::new(0.0, 0.0, 0.0);
+                }
+                let mapped = luminance * (1.0 + luminance / (white * white)) / (1.0 + luminance);
+                color * (mapped / luminance)
+            }
+            ToneMap::Aces { exposure } => {
+                let color = color * exposure.exp2();
+                Color::new(aces(color.r), aces(color.g), aces(color.b))
+            }
+        }
+    }
+}
+
+fn aces(x: f64) -> f64 {
+    let x = x.max(0.0);
+    (x * (2.51 * x + 0.03) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+}
+
+impl<T: Float> Canvas<T> {
+    pub fn tone_map(&mut self, tone_map: ToneMap) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = tone_map.apply(pixel.cast()).cast();
+        }
+    }
+}
+
+#[test]
+fn should_scale_by_exposure_with_linear_tone_map() {
+    let tone_map = ToneMap::Linear { exposure: 1.0 };
+
+    let expected = Color::new(0.5, 1.0, 4.0);
+    let actual = tone_map.apply(Color::new(0.25, 0.5, 2.0));
+
+    assert_eq!(expected, actual);
+
+    let tone_map = ToneMap::Linear { exposure: -2.0 };
+
+    let expected = Color::new(0.25, 0.5, 1.0);
+    let actual = tone_map.apply(Color::new(1.0, 2.0, 4.0));
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_map_white_point_to_white_with_reinhard() {
+    let tone_map = ToneMap::Reinhard {
+        exposure: 0.0,
+        white: 4.0,
+    };
+
+    let expected = Color::new(1.0, 1.0, 1.0);
+    let actual = tone_map.apply(Color::new(4.0, 4.0, 4.0));
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_compress_bright_values_with_reinhard() {
+    let tone_map = ToneMap::Reinhard {
+        exposure: 0.0,
+        white: f64::INFINITY,
+    };
+
+    let expected = Color::new(0.5, 0.5, 0.5);
+    let actual = tone_map.apply(Color::new(1.0, 1.0, 1.0));
+
+    assert_eq!(expected, actual);
+
+    let bright = tone_map.apply(Color::new(1000.0, 1000.0, 1000.0));
+    assert!(bright.r < 1.0);
+}
+
+#[test]
+fn should_preserve_hue_with_reinhard() {
+    let tone_map = ToneMap::Reinhard {
+        exposure: 0.0,
+        white: 10.0,
+    };
+
+    let mapped = tone_map.apply(Color::new(8.0, 4.0, 2.0));
+
+    assert!(crate::equal(mapped.r / mapped.g, 2.0));
+    assert!(crate::equal(mapped.g / mapped.b, 2.0));
+}
+
+#[test]
+fn should_keep_black_black() {
+    let black = Color::new(0.0, 0.0, 0.0);
+    let tone_maps = [
+        ToneMap::Linear { exposure: 3.0 },
+        ToneMap::Reinhard {
+            exposure: 3.0,
+            white: 1.0,
+        },
+        ToneMap::Aces { exposure: 3.0 },
+    ];
+
+    for tone_map in tone_maps {
+        assert_eq!(tone_map.apply(black), black);
+    }
+}
+
+#[test]
+fn should_saturate_towards_white_with_aces() {
+    let tone_map = ToneMap::Aces { exposure: 0.0 };
+
+    let mapped = tone_map.apply(Color::new(0.18, 1.0, 100.0));
+
+    assert_eq!(mapped, Color::new(0.26690, 0.80380, 1.0));
+}
+
+#[test]
+fn should_tone_map_every_pixel_of_a_canvas() {
+    let mut canvas = Canvas::new(2, 1);
+    canvas.set_pixel(1, 0, Color::new(2.0, 4.0, 8.0));
+
+    canvas.tone_map(ToneMap::Linear { exposure: -3.0 });
+
+    assert_eq!(canvas.get_pixel(0, 0), Color::new(0.0, 0.0, 0.0));
+    assert_eq!(canvas.get_pixel(1, 0), Color::new(0.25, 0.5, 1.0));
+}
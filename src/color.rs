@@ -1,38 +1,120 @@
-use crate::equal;
+use crate::approx::ApproxEq;
+use crate::float::Float;
 use std::ops;
 
+/// Transfer function used when colors leave the renderer, the renderer itself
+/// always works with linear values.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum ColorSpace {
+    #[default]
+    Linear,
+    Srgb,
+}
+
+impl ColorSpace {
+    pub fn encode<T: Float>(&self, color: Color<T>) -> Color<T> {
+        match self {
+            ColorSpace::Linear => color,
+            ColorSpace::Srgb => color.to_srgb(),
+        }
+    }
+
+    pub fn decode<T: Float>(&self, color: Color<T>) -> Color<T> {
+        match self {
+            ColorSpace::Linear => color,
+            ColorSpace::Srgb => color.to_linear(),
+        }
+    }
+}
+
+fn srgb_encode<T: Float>(c: T) -> T {
+    let f = T::from_f64;
+    if c <= f(0.0031308) {
+        f(12.92) * c
+    } else {
+        f(1.055) * c.powf(f(1.0 / 2.4)) - f(0.055)
+    }
+}
+
+fn srgb_decode<T: Float>(c: T) -> T {
+    let f = T::from_f64;
+    if c <= f(0.04045) {
+        c / f(12.92)
+    } else {
+        ((c + f(0.055)) / f(1.055)).powf(f(2.4))
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
-pub struct Color {
-    pub r: f64,
-    pub g: f64,
-    pub b: f64,
+pub struct Color<T = f64> {
+    pub r: T,
+    pub g: T,
+    pub b: T,
 }
 
-impl Color {
-    pub fn new(r: f64, g: f64, b: f64) -> Self {
+impl<T: Float> Color<T> {
+    pub fn new(r: T, g: T, b: T) -> Self {
         Self { r, g, b }
     }
 
+    /// Converts to another precision.
+    pub fn cast<U: Float>(&self) -> Color<U> {
+        let convert = |value: T| U::from_f64(value.to_f64());
+        Color::new(convert(self.r), convert(self.g), convert(self.b))
+    }
+
     pub fn is_black(&self) -> bool {
-        self.r == 0.0 && self.g == 0.0 && self.b == 0.0
+        self.r == T::ZERO && self.g == T::ZERO && self.b == T::ZERO
+    }
+
+    /// Relative luminance of a linear Rec. 709 color.
+    pub fn luminance(&self) -> T {
+        let f = T::from_f64;
+        f(0.2126) * self.r + f(0.7152) * self.g + f(0.0722) * self.b
+    }
+
+    /// Applies the sRGB transfer function to a linear color.
+    pub fn to_srgb(self) -> Self {
+        Self::new(
+            srgb_encode(self.r),
+            srgb_encode(self.g),
+            srgb_encode(self.b),
+        )
+    }
+
+    /// Removes the sRGB transfer function from an encoded color.
+    pub fn to_linear(self) -> Self {
+        Self::new(
+            srgb_decode(self.r),
+            srgb_decode(self.g),
+            srgb_decode(self.b),
+        )
     }
 
-    pub fn to_true_color(&self) -> Vec<u8> {
+    pub fn to_true_color(self) -> Vec<u8> {
         vec![
-            (self.r.clamp(0.0, 1.0) * 255.0) as u8,
-            (self.g.clamp(0.0, 1.0) * 255.0) as u8,
-            (self.b.clamp(0.0, 1.0) * 255.0) as u8,
+            (self.r.to_f64().clamp(0.0, 1.0) * 255.0).round() as u8,
+            (self.g.to_f64().clamp(0.0, 1.0) * 255.0).round() as u8,
+            (self.b.to_f64().clamp(0.0, 1.0) * 255.0).round() as u8,
         ]
     }
 }
 
-impl PartialEq for Color {
+impl<T: Float> PartialEq for Color<T> {
     fn eq(&self, other: &Self) -> bool {
-        equal(self.r, other.r) && equal(self.g, other.g) && equal(self.b, other.b)
+        self.r.is_close(other.r) && self.g.is_close(other.g) && self.b.is_close(other.b)
     }
 }
 
-impl ops::Add for Color {
+impl<T: Float> ApproxEq for Color<T> {
+    type Scalar = T;
+
+    fn all_components(&self, other: &Self, eq: impl Fn(T, T) -> bool) -> bool {
+        eq(self.r, other.r) && eq(self.g, other.g) && eq(self.b, other.b)
+    }
+}
+
+impl<T: Float> ops::Add for Color<T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -44,7 +126,7 @@ impl ops::Add for Color {
     }
 }
 
-impl ops::Sub for Color {
+impl<T: Float> ops::Sub for Color<T> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
@@ -56,10 +138,10 @@ impl ops::Sub for Color {
     }
 }
 
-impl ops::Mul<f64> for Color {
+impl<T: Float> ops::Mul<T> for Color<T> {
     type Output = Self;
 
-    fn mul(self, rhs: f64) -> Self::Output {
+    fn mul(self, rhs: T) -> Self::Output {
         Self {
             r: self.r * rhs,
             g: self.g * rhs,
@@ -68,7 +150,7 @@ impl ops::Mul<f64> for Color {
     }
 }
 
-impl ops::Mul for Color {
+impl<T: Float> ops::Mul for Color<T> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
@@ -123,13 +205,19 @@ fn should_multiply_two_colors() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn should_calculate_luminance() {
+    assert_eq!(Color::new(1.0, 1.0, 1.0).luminance(), 1.0);
+    assert_eq!(Color::new(0.0, 1.0, 0.0).luminance(), 0.7152);
+}
+
 #[test]
 fn should_convert_floating_points_to_true_color() {
     let expected = vec![255, 0, 0];
     let actual = Color::new(1.5, 0.0, 0.0).to_true_color();
     assert_eq!(expected, actual);
 
-    let expected = vec![0, 127, 0];
+    let expected = vec![0, 128, 0];
     let actual = Color::new(0.0, 0.5, 0.0).to_true_color();
     assert_eq!(expected, actual);
 
@@ -137,3 +225,58 @@ fn should_convert_floating_points_to_true_color() {
     let actual = Color::new(-0.5, 0.0, 1.0).to_true_color();
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn should_round_to_nearest_true_color() {
+    let expected = vec![1, 0, 254];
+    let actual = Color::new(0.5 / 255.0, 0.49 / 255.0, 253.6 / 255.0).to_true_color();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_encode_linear_color_as_srgb() {
+    let expected = Color::new(0.0, 0.73536, 1.0);
+    let actual = Color::new(0.0, 0.5, 1.0).to_srgb();
+    assert_eq!(expected, actual);
+
+    let expected = Color::new(0.01292, 0.21360, 0.04);
+    let actual = Color::new(0.001, 0.0375, 0.0030960).to_srgb();
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_decode_srgb_color_to_linear() {
+    let expected = Color::new(0.0, 0.21404, 1.0);
+    let actual = Color::new(0.0, 0.5, 1.0).to_linear();
+    assert_eq!(expected, actual);
+
+    let color = Color::new(0.02, 0.3, 0.9);
+    assert_eq!(color.to_srgb().to_linear(), color);
+}
+
+#[test]
+fn should_encode_and_decode_with_color_space() {
+    let color = Color::new(0.2, 0.5, 0.8);
+
+    assert_eq!(ColorSpace::Linear.encode(color), color);
+    assert_eq!(ColorSpace::Srgb.encode(color), color.to_srgb());
+    assert_eq!(ColorSpace::Srgb.decode(color), color.to_linear());
+}
+
+#[test]
+fn should_multiply_colors_in_single_precision() {
+    let color = Color::new(0.9_f32, 0.6, 0.75) * Color::new(0.5, 0.5, 2.0);
+
+    assert_eq!(color, Color::new(0.45, 0.3, 1.5));
+    assert_eq!(color.to_true_color(), vec![115, 77, 255]);
+    assert_eq!(color.cast::<f64>(), Color::new(0.45, 0.3, 1.5));
+}
+
+#[test]
+fn should_compare_colors_exactly() {
+    let color = Color::new(0.1, 0.2, 0.3);
+
+    assert!(color.exact_eq(&Color::new(0.1, 0.2, 0.3)));
+    assert!(!color.exact_eq(&Color::new(0.1, 0.2, 0.300001)));
+    assert_eq!(color, Color::new(0.1, 0.2, 0.300001));
+}
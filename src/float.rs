@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter;
+use std::ops;
+
+/// The floating point types `Point`, `Vector`, `Color`, `Matrix` and `Canvas`
+/// can be built from. They default to `f64`, `f32` halves the memory of large
+/// meshes and framebuffers.
+pub trait Float:
+    Copy
+    + Default
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + ops::Add<Output = Self>
+    + ops::Sub<Output = Self>
+    + ops::Mul<Output = Self>
+    + ops::Div<Output = Self>
+    + ops::Neg<Output = Self>
+    + ops::AddAssign
+    + ops::SubAssign
+    + ops::MulAssign
+    + ops::DivAssign
+    + iter::Sum
+{
+    const ZERO: Self;
+    const ONE: Self;
+    /// The epsilon below which two values count as equal. Larger for `f32`,
+    /// which only has about seven significant digits.
+    const TOLERANCE: Self;
+
+    /// Converts constants, rounding to the nearest `f32`.
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn powf(self, exponent: Self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn clamp(self, min: Self, max: Self) -> Self;
+    fn copysign(self, sign: Self) -> Self;
+    fn total_cmp(&self, other: &Self) -> Ordering;
+    fn is_nan(self) -> bool;
+    /// How many representable values lie between the two, 0 for equal
+    /// values and for 0 and -0.
+    fn ulps_between(self, other: Self) -> u64;
+
+    fn sin_cos(self) -> (Self, Self) {
+        (self.sin(), self.cos())
+    }
+
+    /// Whether the values differ by less than `TOLERANCE`.
+    fn is_close(self, other: Self) -> bool {
+        (self - other).abs() < Self::TOLERANCE
+    }
+}
+
+macro_rules! impl_float {
+    ($type:ident, $bits:ident, $tolerance:expr) => {
+        impl Float for $type {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const TOLERANCE: Self = $tolerance;
+
+            fn from_f64(value: f64) -> Self {
+                value as $type
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn abs(self) -> Self {
+                $type::abs(self)
+            }
+
+            fn sqrt(self) -> Self {
+                $type::sqrt(self)
+            }
+
+            fn powf(self, exponent: Self) -> Self {
+                $type::powf(self, exponent)
+            }
+
+            fn sin(self) -> Self {
+                $type::sin(self)
+            }
+
+            fn cos(self) -> Self {
+                $type::cos(self)
+            }
+
+            fn tan(self) -> Self {
+                $type::tan(self)
+            }
+
+            fn asin(self) -> Self {
+                $type::asin(self)
+            }
+
+            fn acos(self) -> Self {
+                $type::acos(self)
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                $type::atan2(self, other)
+            }
+
+            fn min(self, other: Self) -> Self {
+                $type::min(self, other)
+            }
+
+            fn max(self, other: Self) -> Self {
+                $type::max(self, other)
+            }
+
+            fn clamp(self, min: Self, max: Self) -> Self {
+                $type::clamp(self, min, max)
+            }
+
+            fn copysign(self, sign: Self) -> Self {
+                $type::copysign(self, sign)
+            }
+
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                $type::total_cmp(self, other)
+            }
+
+            fn is_nan(self) -> bool {
+                $type::is_nan(self)
+            }
+
+            fn ulps_between(self, other: Self) -> u64 {
+                // maps sign and magnitude to integers counting up through
+                // the floats in order, with -0 and 0 both at 0
+                let ordered = |value: $type| {
+                    let bits = value.to_bits() as $bits;
+                    if bits < 0 {
+                        $bits::MIN - bits
+                    } else {
+                        bits
+                    }
+                };
+                (ordered(self) as i64).abs_diff(ordered(other) as i64)
+            }
+
+            fn sin_cos(self) -> (Self, Self) {
+                $type::sin_cos(self)
+            }
+        }
+    };
+}
+
+impl_float!(f32, i32, 0.0001);
+impl_float!(f64, i64, crate::EPSILON);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hypotenuse<T: Float>(a: T, b: T) -> T {
+        (a * a + b * b).sqrt()
+    }
+
+    #[test]
+    fn should_compute_with_either_precision() {
+        assert_eq!(hypotenuse(3.0_f32, 4.0), 5.0);
+        assert_eq!(hypotenuse(3.0_f64, 4.0), 5.0);
+        assert_eq!(f32::from_f64(0.1), 0.1_f32);
+    }
+
+    #[test]
+    fn should_compare_with_tolerance_of_the_type() {
+        assert!(1.0_f64.is_close(1.000001));
+        assert!(!1.0_f64.is_close(1.0001));
+        assert!(1.0_f32.is_close(1.00005));
+        assert!(!1.0_f32.is_close(1.001));
+    }
+
+    #[test]
+    fn should_count_ulps_between_values() {
+        let next = f64::from_bits(1.0_f64.to_bits() + 3);
+
+        assert_eq!(1.0_f64.ulps_between(next), 3);
+        assert_eq!(next.ulps_between(1.0), 3);
+        assert_eq!(0.0_f64.ulps_between(-0.0), 0);
+        assert_eq!(f32::from_bits(1).ulps_between(-f32::from_bits(1)), 2);
+    }
+}
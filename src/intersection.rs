@@ -0,0 +1,164 @@
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::sphere::Sphere;
+use crate::vector::Vector;
+use crate::EPSILON;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Intersection<'a> {
+    pub t: f64,
+    pub object: &'a Sphere,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(t: f64, object: &'a Sphere) -> Self {
+        Self { t, object }
+    }
+
+    pub fn prepare_computations(&self, ray: &Ray) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at(point);
+        let inside = normalv.dot(&eyev) < 0.0;
+        if inside {
+            normalv = -normalv;
+        }
+
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            over_point: point + normalv * EPSILON,
+            eyev,
+            normalv,
+            reflectv: ray.direction.reflect(&normalv),
+            inside,
+        }
+    }
+}
+
+/// Returns the visible intersection, the one with the lowest non-negative `t`.
+pub fn hit<'a, 'b>(intersections: &'b [Intersection<'a>]) -> Option<&'b Intersection<'a>> {
+    intersections
+        .iter()
+        .filter(|intersection| intersection.t >= 0.0)
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+}
+
+/// Everything needed to shade an intersection, precomputed once.
+#[derive(Debug, Copy, Clone)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a Sphere,
+    pub point: Point,
+    /// `point` nudged along the normal, so shadow rays don't hit the surface
+    /// they start on.
+    pub over_point: Point,
+    pub eyev: Vector,
+    pub normalv: Vector,
+    pub reflectv: Vector,
+    pub inside: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Material;
+    use crate::matrix::Matrix;
+
+    #[test]
+    fn should_hit_when_all_intersections_are_positive() {
+        let sphere = Sphere::default();
+        let i1 = Intersection::new(1.0, &sphere);
+        let i2 = Intersection::new(2.0, &sphere);
+
+        assert_eq!(hit(&[i2, i1]), Some(&i1));
+    }
+
+    #[test]
+    fn should_hit_when_some_intersections_are_negative() {
+        let sphere = Sphere::default();
+        let i1 = Intersection::new(-1.0, &sphere);
+        let i2 = Intersection::new(1.0, &sphere);
+
+        assert_eq!(hit(&[i2, i1]), Some(&i2));
+    }
+
+    #[test]
+    fn should_not_hit_when_all_intersections_are_negative() {
+        let sphere = Sphere::default();
+        let i1 = Intersection::new(-2.0, &sphere);
+        let i2 = Intersection::new(-1.0, &sphere);
+
+        assert_eq!(hit(&[i2, i1]), None);
+    }
+
+    #[test]
+    fn should_hit_lowest_non_negative_intersection() {
+        let sphere = Sphere::default();
+        let i1 = Intersection::new(5.0, &sphere);
+        let i2 = Intersection::new(7.0, &sphere);
+        let i3 = Intersection::new(-3.0, &sphere);
+        let i4 = Intersection::new(2.0, &sphere);
+
+        assert_eq!(hit(&[i1, i2, i3, i4]), Some(&i4));
+    }
+
+    #[test]
+    fn should_precompute_state_of_an_intersection() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::default();
+        let intersection = Intersection::new(4.0, &sphere);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(comps.t, 4.0);
+        assert_eq!(comps.object, &sphere);
+        assert_eq!(comps.point, Point::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.eyev, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv, Vector::new(0.0, 0.0, -1.0));
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn should_precompute_reflection_vector() {
+        let half_sqrt = 2.0_f64.sqrt() / 2.0;
+        let ray = Ray::new(
+            Point::new(0.0, 1.0, -1.0),
+            Vector::new(0.0, -half_sqrt, half_sqrt),
+        );
+        let sphere = Sphere::new(Matrix::translation(0.0, -1.0, 0.0), Material::default());
+        let intersection = Intersection::new(2.0_f64.sqrt(), &sphere);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(comps.normalv, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(comps.reflectv, Vector::new(0.0, half_sqrt, half_sqrt));
+    }
+
+    #[test]
+    fn should_flip_normal_when_hit_is_inside() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::default();
+        let intersection = Intersection::new(1.0, &sphere);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(comps.point, Point::new(0.0, 0.0, 1.0));
+        assert_eq!(comps.eyev, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv, Vector::new(0.0, 0.0, -1.0));
+        assert!(comps.inside);
+    }
+
+    #[test]
+    fn should_offset_the_point_of_a_hit() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new(Matrix::translation(0.0, 0.0, 1.0), Material::default());
+        let intersection = Intersection::new(5.0, &sphere);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert!(comps.over_point.z < -EPSILON / 2.0);
+        assert!(comps.point.z > comps.over_point.z);
+    }
+}
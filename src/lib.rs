@@ -1,21 +1,43 @@
 #![allow(dead_code, unused_must_use)]
 extern crate core;
 
+mod approx;
+mod camera;
 mod canvas;
 mod color;
+mod float;
+mod intersection;
+mod light;
+mod material;
 mod matrix;
 mod point;
+mod quaternion;
+mod random;
+mod ray;
+mod scene;
+mod sphere;
+mod transform;
+mod tuple;
 mod vector;
-
-use crate::point::Point;
-use crate::vector::Vector;
-
+mod world;
+
+pub use crate::approx::ApproxEq;
+pub use crate::camera::{Camera, CancellationToken, Cancelled, Progress, Region, Sampling};
+pub use crate::canvas::{Canvas, ImageError, ImageFormat};
+pub use crate::color::{Color, ColorSpace};
+pub use crate::float::Float;
+pub use crate::matrix::Matrix;
+pub use crate::point::Point;
+pub use crate::scene::{Scene, SceneError};
+pub use crate::vector::Vector;
+pub use crate::world::{World, MAX_DEPTH};
+
+pub const EPSILON: f64 = 0.00001;
+
+/// The absolute comparison behind `PartialEq` of the math types. `ApproxEq`
+/// offers relative and ULP tolerances where a fixed epsilon doesn't fit.
 pub fn equal(f1: f64, f2: f64) -> bool {
-    const EPSILON: f64 = 0.00001;
-    if (f1 - f2).abs() < EPSILON {
-        return true;
-    }
-    false
+    f1.is_close(f2)
 }
 
 #[derive(Debug)]
@@ -79,6 +101,7 @@ mod test {
         println!("Cannonball went {:#?} meters!", projectile.position.x);
         println!("Finished after {} ticks", i);
 
-        canvas.write_to_file().expect("Could not write to file")
+        let path = std::env::temp_dir().join("fire_virtual_cannon.ppm");
+        canvas.save(path).expect("Could not write to file")
     }
 }
@@ -0,0 +1,460 @@
+use crate::color::Color;
+use crate::point::Point;
+use crate::random::Random;
+use crate::vector::Vector;
+
+/// How light fades with distance: `1 / (constant + linear * d + quadratic * d²)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Attenuation {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+impl Attenuation {
+    pub fn new(constant: f64, linear: f64, quadratic: f64) -> Self {
+        Self {
+            constant,
+            linear,
+            quadratic,
+        }
+    }
+
+    /// Physically based falloff with the square of the distance.
+    pub fn inverse_square() -> Self {
+        Self::new(0.0, 0.0, 1.0)
+    }
+
+    pub fn factor(&self, distance: f64) -> f64 {
+        1.0 / (self.constant + self.linear * distance + self.quadratic * distance * distance)
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self::new(1.0, 0.0, 0.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PointLight {
+    pub position: Point,
+    pub intensity: Color,
+    pub attenuation: Attenuation,
+}
+
+impl PointLight {
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            attenuation: Attenuation::default(),
+        }
+    }
+}
+
+/// A light infinitely far away, like the sun. `direction` is the direction
+/// the light travels in.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DirectionalLight {
+    pub direction: Vector,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, intensity: Color) -> Self {
+        Self {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+/// A point light limited to a cone around `direction`. Everything inside
+/// `inner_angle` is fully lit, the light fades out towards `outer_angle`
+/// with `falloff` as the exponent of the fade.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub falloff: f64,
+    pub intensity: Color,
+    pub attenuation: Attenuation,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point,
+        direction: Vector,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            falloff: 1.0,
+            intensity,
+            attenuation: Attenuation::default(),
+        }
+    }
+
+    fn cone_factor(&self, lightv: Vector) -> f64 {
+        let cos_angle = (-lightv).dot(&self.direction);
+        let (cos_inner, cos_outer) = (self.inner_angle.cos(), self.outer_angle.cos());
+        if cos_angle >= cos_inner {
+            1.0
+        } else if cos_angle <= cos_outer {
+            0.0
+        } else {
+            ((cos_angle - cos_outer) / (cos_inner - cos_outer)).powf(self.falloff)
+        }
+    }
+}
+
+/// A rectangular light spanned by two edges from `corner`. The rectangle is
+/// split into `usteps` by `vsteps` cells and each cell is one shadow sample.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AreaLight {
+    pub corner: Point,
+    pub uvec: Vector,
+    pub usteps: usize,
+    pub vvec: Vector,
+    pub vsteps: usize,
+    pub intensity: Color,
+    /// Moves every sample to a random spot inside its cell instead of the
+    /// cell center, which trades banding in the penumbra for noise.
+    pub jitter: bool,
+}
+
+impl AreaLight {
+    /// Panics if either edge has no steps, a light needs at least one sample.
+    pub fn new(
+        corner: Point,
+        full_uvec: Vector,
+        usteps: usize,
+        full_vvec: Vector,
+        vsteps: usize,
+        intensity: Color,
+    ) -> Self {
+        assert!(
+            usteps > 0 && vsteps > 0,
+            "tried to split an area light into zero steps"
+        );
+        Self {
+            corner,
+            uvec: full_uvec / usteps as f64,
+            usteps,
+            vvec: full_vvec / vsteps as f64,
+            vsteps,
+            intensity,
+            jitter: false,
+        }
+    }
+
+    pub fn samples(&self) -> usize {
+        self.usteps * self.vsteps
+    }
+
+    /// The center of the light.
+    pub fn position(&self) -> Point {
+        self.corner
+            + self.uvec * (self.usteps as f64 / 2.0)
+            + self.vvec * (self.vsteps as f64 / 2.0)
+    }
+
+    fn point_on_light(&self, u: usize, v: usize, random: &mut Random) -> Point {
+        let (du, dv) = if self.jitter {
+            (random.next_f64(), random.next_f64())
+        } else {
+            (0.5, 0.5)
+        };
+        self.corner + self.uvec * (u as f64 + du) + self.vvec * (v as f64 + dv)
+    }
+
+    /// Every sample position on the light as seen from `point`. Jittered
+    /// positions are derived from `point`, so renders are reproducible.
+    pub fn sample_points(&self, point: Point) -> Vec<Point> {
+        let seed = point.x.to_bits()
+            ^ point.y.to_bits().rotate_left(21)
+            ^ point.z.to_bits().rotate_left(42);
+        let mut random = Random::new(seed);
+
+        (0..self.vsteps)
+            .flat_map(|v| (0..self.usteps).map(move |u| (u, v)))
+            .map(|(u, v)| self.point_on_light(u, v, &mut random))
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Light {
+    Point(PointLight),
+    Directional(DirectionalLight),
+    Spot(SpotLight),
+    Area(AreaLight),
+}
+
+impl Light {
+    pub fn intensity(&self) -> Color {
+        match self {
+            Light::Point(light) => light.intensity,
+            Light::Directional(light) => light.intensity,
+            Light::Spot(light) => light.intensity,
+            Light::Area(light) => light.intensity,
+        }
+    }
+
+    /// Unit vectors from `point` towards every sample on the light, together
+    /// with the distance to that sample. Directional lights are infinitely
+    /// far away.
+    pub fn samples(&self, point: Point) -> Vec<(Vector, f64)> {
+        let towards = |position: Point| {
+            let to_light = position - point;
+            let distance = to_light.magnitude();
+            (to_light / distance, distance)
+        };
+
+        match self {
+            Light::Point(light) => vec![towards(light.position)],
+            Light::Spot(light) => vec![towards(light.position)],
+            Light::Directional(light) => vec![(-light.direction, f64::INFINITY)],
+            Light::Area(light) => light
+                .sample_points(point)
+                .into_iter()
+                .map(towards)
+                .collect(),
+        }
+    }
+
+    /// How much of the light's intensity arrives along `lightv` after
+    /// travelling `distance`, from distance attenuation and the spot cone.
+    pub fn falloff(&self, lightv: Vector, distance: f64) -> f64 {
+        match self {
+            Light::Point(light) => light.attenuation.factor(distance),
+            Light::Spot(light) => light.attenuation.factor(distance) * light.cone_factor(lightv),
+            Light::Directional(_) | Light::Area(_) => 1.0,
+        }
+    }
+
+    /// Returns the fraction of light samples visible from `point`, so 0.0 is
+    /// full shadow, 1.0 is fully lit and anything in between is penumbra.
+    /// `occluded` is asked whether anything blocks the way from `point` along
+    /// the unit vector towards the light within the given distance.
+    pub fn intensity_at(&self, point: Point, occluded: impl Fn(Point, Vector, f64) -> bool) -> f64 {
+        let samples = self.samples(point);
+        let visible = samples
+            .iter()
+            .filter(|(lightv, distance)| !occluded(point, *lightv, *distance))
+            .count();
+        visible as f64 / samples.len() as f64
+    }
+}
+
+impl From<PointLight> for Light {
+    fn from(light: PointLight) -> Self {
+        Light::Point(light)
+    }
+}
+
+impl From<DirectionalLight> for Light {
+    fn from(light: DirectionalLight) -> Self {
+        Light::Directional(light)
+    }
+}
+
+impl From<SpotLight> for Light {
+    fn from(light: SpotLight) -> Self {
+        Light::Spot(light)
+    }
+}
+
+impl From<AreaLight> for Light {
+    fn from(light: AreaLight) -> Self {
+        Light::Area(light)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equal;
+    use std::f64::consts::PI;
+
+    fn white() -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn area_light() -> AreaLight {
+        AreaLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(2.0, 0.0, 0.0),
+            4,
+            Vector::new(0.0, 0.0, 1.0),
+            2,
+            white(),
+        )
+    }
+
+    #[test]
+    fn should_create_a_point_light() {
+        let light = PointLight::new(Point::new(0.0, 0.0, 0.0), white());
+
+        assert_eq!(light.position, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(light.intensity, white());
+        assert_eq!(light.attenuation, Attenuation::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_be_fully_lit_or_fully_shadowed_by_a_point_light() {
+        let light = Light::from(PointLight::new(Point::new(0.0, 10.0, 0.0), white()));
+        let point = Point::new(0.0, 0.0, 0.0);
+
+        assert_eq!(light.intensity_at(point, |_, _, _| false), 1.0);
+        assert_eq!(light.intensity_at(point, |_, _, _| true), 0.0);
+    }
+
+    #[test]
+    fn should_sample_direction_and_distance_to_a_point_light() {
+        let light = Light::from(PointLight::new(Point::new(0.0, 10.0, 0.0), white()));
+
+        let samples = light.samples(Point::new(0.0, 2.0, 0.0));
+
+        assert_eq!(samples, vec![(Vector::new(0.0, 1.0, 0.0), 8.0)]);
+    }
+
+    #[test]
+    fn should_attenuate_with_distance() {
+        let mut point_light = PointLight::new(Point::new(0.0, 0.0, 0.0), white());
+        point_light.attenuation = Attenuation::inverse_square();
+        let light = Light::from(point_light);
+
+        assert_eq!(light.falloff(Vector::new(0.0, 1.0, 0.0), 2.0), 0.25);
+
+        let attenuation = Attenuation::new(1.0, 0.5, 0.25);
+        assert_eq!(attenuation.factor(2.0), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn should_shine_from_infinitely_far_with_directional_light() {
+        let light = Light::from(DirectionalLight::new(Vector::new(0.0, -2.0, 0.0), white()));
+
+        let samples = light.samples(Point::new(5.0, 0.0, -3.0));
+
+        assert_eq!(samples, vec![(Vector::new(0.0, 1.0, 0.0), f64::INFINITY)]);
+        assert_eq!(
+            light.falloff(Vector::new(0.0, 1.0, 0.0), f64::INFINITY),
+            1.0
+        );
+        assert_eq!(
+            light.intensity_at(Point::new(0.0, 0.0, 0.0), |_, _, distance| distance < 100.0),
+            1.0
+        );
+    }
+
+    #[test]
+    fn should_only_light_inside_spot_light_cone() {
+        let mut spot_light = SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.0, -1.0, 0.0),
+            PI / 8.0,
+            PI / 4.0,
+            white(),
+        );
+        spot_light.falloff = 2.0;
+        let light = Light::from(spot_light);
+
+        let inside = Vector::new(0.0, 1.0, 0.0);
+        let outside = Vector::new(1.0, 1.0, 0.0).normalize();
+        let between = Vector::new(-(3.0 * PI / 16.0).sin(), (3.0 * PI / 16.0).cos(), 0.0);
+
+        assert_eq!(light.falloff(inside, 10.0), 1.0);
+        assert_eq!(light.falloff(outside, 10.0), 0.0);
+
+        let cos_inner = (PI / 8.0).cos();
+        let cos_outer = (PI / 4.0).cos();
+        let expected = (((3.0 * PI / 16.0).cos() - cos_outer) / (cos_inner - cos_outer)).powi(2);
+        assert!(equal(light.falloff(between, 10.0), expected));
+    }
+
+    #[test]
+    fn should_create_an_area_light() {
+        let light = area_light();
+
+        assert_eq!(light.corner, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(light.uvec, Vector::new(0.5, 0.0, 0.0));
+        assert_eq!(light.usteps, 4);
+        assert_eq!(light.vvec, Vector::new(0.0, 0.0, 0.5));
+        assert_eq!(light.vsteps, 2);
+        assert_eq!(light.samples(), 8);
+        assert_eq!(light.position(), Point::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "zero steps")]
+    fn should_panic_when_area_light_has_no_steps() {
+        AreaLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(2.0, 0.0, 0.0),
+            0,
+            Vector::new(0.0, 0.0, 1.0),
+            2,
+            Color::new(1.0, 1.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn should_find_a_single_point_on_an_area_light() {
+        let light = area_light();
+        let mut random = Random::new(0);
+
+        let cells = [
+            ((0, 0), Point::new(0.25, 0.0, 0.25)),
+            ((1, 0), Point::new(0.75, 0.0, 0.25)),
+            ((0, 1), Point::new(0.25, 0.0, 0.75)),
+            ((2, 0), Point::new(1.25, 0.0, 0.25)),
+            ((3, 1), Point::new(1.75, 0.0, 0.75)),
+        ];
+
+        for ((u, v), expected) in cells {
+            assert_eq!(light.point_on_light(u, v, &mut random), expected);
+        }
+    }
+
+    #[test]
+    fn should_keep_jittered_points_inside_their_cell() {
+        let mut light = area_light();
+        light.jitter = true;
+
+        let samples = light.sample_points(Point::new(1.0, -1.0, 3.0));
+
+        assert_eq!(samples.len(), 8);
+        for (index, sample) in samples.iter().enumerate() {
+            let (u, v) = ((index % 4) as f64, (index / 4) as f64);
+            assert!(sample.x >= u * 0.5 && sample.x < (u + 1.0) * 0.5);
+            assert!(sample.z >= v * 0.5 && sample.z < (v + 1.0) * 0.5);
+        }
+        assert_ne!(samples[0], Point::new(0.25, 0.0, 0.25));
+    }
+
+    #[test]
+    fn should_calculate_fraction_of_visible_light_samples() {
+        let light = Light::from(area_light());
+        let point = Point::new(1.0, -2.0, 0.5);
+
+        assert_eq!(light.intensity_at(point, |_, _, _| false), 1.0);
+        assert_eq!(light.intensity_at(point, |_, _, _| true), 0.0);
+        assert_eq!(
+            light.intensity_at(point, |_, lightv, _| lightv.x < 0.0),
+            0.5
+        );
+        assert_eq!(
+            light.intensity_at(point, |_, lightv, _| lightv.x < -0.2 && lightv.z < 0.0),
+            0.875
+        );
+    }
+}
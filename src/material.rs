@@ -0,0 +1,308 @@
+use crate::color::Color;
+use crate::light::Light;
+use crate::point::Point;
+use crate::vector::Vector;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Material {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    /// How much of the surrounding scene the surface mirrors, from 0 to 1.
+    pub reflective: f64,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            color: Color::new(1.0, 1.0, 1.0),
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.9,
+            shininess: 200.0,
+            reflective: 0.0,
+        }
+    }
+}
+
+/// Phong shading of `point` as seen along `eyev`. `intensity` is the fraction
+/// of the light that reaches the point, as returned by `Light::intensity_at`.
+pub fn lighting(
+    material: &Material,
+    light: &Light,
+    point: Point,
+    eyev: Vector,
+    normalv: Vector,
+    intensity: f64,
+) -> Color {
+    let effective_color = material.color * light.intensity();
+    let ambient = effective_color * material.ambient;
+
+    let samples = light.samples(point);
+    let mut sum = Color::new(0.0, 0.0, 0.0);
+    for (lightv, distance) in samples.iter() {
+        let light_dot_normal = lightv.dot(&normalv);
+        if light_dot_normal < 0.0 {
+            continue;
+        }
+
+        let diffuse = effective_color * material.diffuse * light_dot_normal;
+        let reflect_dot_eye = (-*lightv).reflect(&normalv).dot(&eyev);
+        let specular = if reflect_dot_eye <= 0.0 {
+            Color::new(0.0, 0.0, 0.0)
+        } else {
+            light.intensity() * material.specular * reflect_dot_eye.powf(material.shininess)
+        };
+
+        sum = sum + (diffuse + specular) * light.falloff(*lightv, *distance);
+    }
+
+    ambient + sum * (intensity / samples.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::light::{AreaLight, DirectionalLight, PointLight, SpotLight};
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+    fn white() -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn point_light(x: f64, y: f64, z: f64) -> Light {
+        Light::from(PointLight::new(Point::new(x, y, z), white()))
+    }
+
+    #[test]
+    fn should_create_default_material() {
+        let m = Material::default();
+
+        assert_eq!(m.color, white());
+        assert_eq!(m.ambient, 0.1);
+        assert_eq!(m.diffuse, 0.9);
+        assert_eq!(m.specular, 0.9);
+        assert_eq!(m.shininess, 200.0);
+    }
+
+    #[test]
+    fn should_light_with_eye_between_light_and_surface() {
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = point_light(0.0, 0.0, -10.0);
+
+        let actual = lighting(
+            &Material::default(),
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(actual, Color::new(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn should_light_with_eye_offset_45_degrees() {
+        let half_sqrt = 2.0_f64.sqrt() / 2.0;
+        let eyev = Vector::new(0.0, half_sqrt, -half_sqrt);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = point_light(0.0, 0.0, -10.0);
+
+        let actual = lighting(
+            &Material::default(),
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(actual, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn should_light_with_light_offset_45_degrees() {
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = point_light(0.0, 10.0, -10.0);
+
+        let actual = lighting(
+            &Material::default(),
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(actual, Color::new(0.7364, 0.7364, 0.7364));
+    }
+
+    #[test]
+    fn should_light_with_eye_in_path_of_reflection() {
+        let half_sqrt = 2.0_f64.sqrt() / 2.0;
+        let eyev = Vector::new(0.0, -half_sqrt, -half_sqrt);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = point_light(0.0, 10.0, -10.0);
+
+        let actual = lighting(
+            &Material::default(),
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(actual, Color::new(1.6364, 1.6364, 1.6364));
+    }
+
+    #[test]
+    fn should_light_with_light_behind_surface() {
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = point_light(0.0, 0.0, 10.0);
+
+        let actual = lighting(
+            &Material::default(),
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(actual, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn should_light_with_surface_in_shadow() {
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = point_light(0.0, 0.0, -10.0);
+
+        let actual = lighting(
+            &Material::default(),
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            eyev,
+            normalv,
+            0.0,
+        );
+
+        assert_eq!(actual, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn should_scale_diffuse_and_specular_by_light_intensity() {
+        let light = point_light(0.0, 0.0, -10.0);
+        let material = Material {
+            specular: 0.0,
+            ..Material::default()
+        };
+        let point = Point::new(0.0, 0.0, -1.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+
+        for (intensity, expected) in [(1.0, 1.0), (0.5, 0.55), (0.0, 0.1)] {
+            let actual = lighting(&material, &light, point, eyev, normalv, intensity);
+            assert_eq!(actual, Color::new(expected, expected, expected));
+        }
+    }
+
+    #[test]
+    fn should_sample_an_area_light_when_lighting() {
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        let light = Light::from(AreaLight::new(corner, v1, 2, v2, 2, white()));
+        let material = Material {
+            specular: 0.0,
+            ..Material::default()
+        };
+        let eye = Point::new(0.0, 0.0, -5.0);
+
+        let cases = [
+            (Point::new(0.0, 0.0, -1.0), 0.9965),
+            (Point::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2), 0.62318),
+        ];
+
+        for (point, expected) in cases {
+            let eyev = (eye - point).normalize();
+            let normalv = Vector::new(point.x, point.y, point.z);
+            let actual = lighting(&material, &light, point, eyev, normalv, 1.0);
+            assert_eq!(actual, Color::new(expected, expected, expected));
+        }
+    }
+
+    #[test]
+    fn should_light_with_directional_light() {
+        let light = Light::from(DirectionalLight::new(Vector::new(0.0, -1.0, 1.0), white()));
+        let material = Material {
+            specular: 0.0,
+            ..Material::default()
+        };
+        let normalv = Vector::new(0.0, 1.0, 0.0);
+
+        let near = lighting(
+            &material,
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            normalv,
+            normalv,
+            1.0,
+        );
+        let far = lighting(
+            &material,
+            &light,
+            Point::new(1000.0, 0.0, -500.0),
+            normalv,
+            normalv,
+            1.0,
+        );
+
+        let expected = 0.1 + 0.9 * 2.0_f64.sqrt() / 2.0;
+        assert_eq!(near, Color::new(expected, expected, expected));
+        assert_eq!(near, far);
+    }
+
+    #[test]
+    fn should_light_only_inside_spot_light_cone() {
+        let light = Light::from(SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.0, -1.0, 0.0),
+            PI / 16.0,
+            PI / 8.0,
+            white(),
+        ));
+        let material = Material {
+            specular: 0.0,
+            ..Material::default()
+        };
+        let normalv = Vector::new(0.0, 1.0, 0.0);
+
+        let inside = lighting(
+            &material,
+            &light,
+            Point::new(0.0, 0.0, 0.0),
+            normalv,
+            normalv,
+            1.0,
+        );
+        let outside = lighting(
+            &material,
+            &light,
+            Point::new(10.0, 0.0, 0.0),
+            normalv,
+            normalv,
+            1.0,
+        );
+
+        assert_eq!(inside, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(outside, Color::new(0.1, 0.1, 0.1));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::equal;
+pub mod decompose;
+
+use crate::approx::ApproxEq;
+use crate::float::Float;
 use std::fmt;
 use std::ops;
 
@@ -6,171 +9,257 @@ use crate::point::Point;
 use crate::vector::Vector;
 
 #[derive(Copy, Clone)]
-struct Matrix<const D: usize> {
-    entries: [[f64; D]; D],
+pub struct Matrix<const D: usize, T = f64> {
+    entries: [[T; D]; D],
 }
 
-impl<const D: usize> From<[[f64; D]; D]> for Matrix<D> {
-    fn from(entries: [[f64; D]; D]) -> Self {
+impl<const D: usize, T> From<[[T; D]; D]> for Matrix<D, T> {
+    fn from(entries: [[T; D]; D]) -> Self {
         Matrix { entries }
     }
 }
 
-impl<const D: usize> Matrix<D> {
-    fn new() -> Matrix<D> {
-        Matrix::from([[0.0; D]; D])
-    }
-
-    fn transpose(&self) -> Self {
-        let mut entries = [[0.0; D]; D];
-        for row in 0..D {
-            for col in 0..D {
-                entries[col][row] = self.entries[row][col]
-            }
-        }
-        Self { entries }
+impl<const D: usize, T: Float> Default for Matrix<D, T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl Matrix<2> {
-    fn determinant(&self) -> f64 {
-        self[0][0] * self[1][1] - self[1][0] * self[0][1]
+impl<const D: usize, T: Float> Matrix<D, T> {
+    pub fn new() -> Self {
+        Matrix::from([[T::ZERO; D]; D])
     }
-    fn is_invertible(&self) -> bool {
-        self.determinant() != 0.0
-    }
-}
 
-impl Matrix<3> {
-    fn determinant(&self) -> f64 {
-        let mut determinant = 0.0;
-        for (index, element) in self[0].iter().enumerate() {
-            determinant += element * self.cofactor(0, index);
+    pub fn identity() -> Self {
+        let mut matrix = Matrix::new();
+        for i in 0..D {
+            matrix[i][i] = T::ONE;
         }
-        determinant
+        matrix
     }
 
-    fn submatrix(&self, row: usize, col: usize) -> Matrix<2> {
-        let mut submatrix = Matrix::new();
-        let mut i = 0;
-        let mut j = 0;
-
-        //TODO: Can I write self.iter() here?
-        for (index, element) in self.entries.iter().enumerate() {
-            if index == row {
-                continue;
+    pub fn transpose(&self) -> Self {
+        let mut entries = [[T::ZERO; D]; D];
+        for (row, values) in self.entries.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                entries[col][row] = *value
             }
-            for (index, entry) in element.iter().enumerate() {
-                if index == col {
-                    continue;
-                }
+        }
+        Self { entries }
+    }
 
-                submatrix[i][j] = *entry;
+    /// Converts every entry to another precision.
+    pub fn cast<U: Float>(&self) -> Matrix<D, U> {
+        Matrix::from(
+            self.entries
+                .map(|row| row.map(|entry| U::from_f64(entry.to_f64()))),
+        )
+    }
 
-                j = match j {
-                    1 => 0,
-                    _ => 1,
-                };
-            }
+    /// Removes `row` and `col`. `E` has to be one less than `D`, which the
+    /// type system can't express yet.
+    pub fn submatrix<const E: usize>(&self, row: usize, col: usize) -> Matrix<E, T> {
+        assert_eq!(E + 1, D, "a submatrix has one row and column less");
 
-            i = match i {
-                1 => 0,
-                _ => 1,
-            };
+        let mut submatrix = Matrix::new();
+        let rows = (0..D).filter(|r| *r != row);
+        for (i, r) in rows.enumerate() {
+            let cols = (0..D).filter(|c| *c != col);
+            for (j, c) in cols.enumerate() {
+                submatrix[i][j] = self.entries[r][c];
+            }
         }
         submatrix
     }
 
-    fn minor(&self, row: usize, col: usize) -> f64 {
-        self.submatrix(row, col).determinant()
-    }
-
-    fn cofactor(&self, row: usize, col: usize) -> f64 {
-        let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
-            minor
+    /// The determinant of the submatrix without `row` and `col`.
+    pub fn minor(&self, row: usize, col: usize) -> T {
+        let cofactor = self.cofactor(row, col);
+        if (row + col).is_multiple_of(2) {
+            cofactor
         } else {
-            -minor
+            -cofactor
         }
     }
 
-    fn is_invertible(&self) -> bool {
-        self.determinant() != 0.0
+    /// The signed minor. Replacing `row` with the unit row that has its 1 in
+    /// `col` leaves just this term in the expansion of the determinant along
+    /// `row`, which works for any size without building the submatrix.
+    pub fn cofactor(&self, row: usize, col: usize) -> T {
+        let mut matrix = *self;
+        matrix.entries[row] = [T::ZERO; D];
+        matrix.entries[row][col] = T::ONE;
+        matrix.determinant()
+    }
+
+    /// LU decomposition with partial pivoting. The returned matrix holds `U`
+    /// on and above the diagonal and `L`, without its unit diagonal, below.
+    /// Row `i` of it comes from row `permutation[i]` of this matrix and
+    /// `sign` is -1 after an odd number of row swaps. Returns `None` if the
+    /// matrix is singular.
+    fn lu(&self) -> Option<(Self, [usize; D], T)> {
+        let mut lu = *self;
+        let mut permutation = [0; D];
+        for (i, row) in permutation.iter_mut().enumerate() {
+            *row = i;
+        }
+        let mut sign = T::ONE;
+
+        for k in 0..D {
+            let pivot = (k..D).max_by(|a, b| lu[*a][k].abs().total_cmp(&lu[*b][k].abs()))?;
+            if lu[pivot][k] == T::ZERO {
+                return None;
+            }
+            if pivot != k {
+                lu.entries.swap(pivot, k);
+                permutation.swap(pivot, k);
+                sign = -sign;
+            }
+
+            for i in k + 1..D {
+                let factor = lu[i][k] / lu[k][k];
+                lu[i][k] = factor;
+                for j in k + 1..D {
+                    let above = lu[k][j];
+                    lu[i][j] -= factor * above;
+                }
+            }
+        }
+
+        Some((lu, permutation, sign))
     }
-}
 
-impl Matrix<4> {
-    fn determinant(&self) -> f64 {
-        let mut determinant = 0.0;
-        for (index, element) in self[0].iter().enumerate() {
-            determinant += element * self.cofactor(0, index);
+    /// Exact formulas up to 4x4, an LU decomposition for larger matrices.
+    pub fn determinant(&self) -> T {
+        let m = &self.entries;
+        match D {
+            0 => T::ONE,
+            1 => m[0][0],
+            2 => m[0][0] * m[1][1] - m[1][0] * m[0][1],
+            3 => {
+                m[0][0] * (m[1][1] * m[2][2] - m[2][1] * m[1][2])
+                    - m[0][1] * (m[1][0] * m[2][2] - m[2][0] * m[1][2])
+                    + m[0][2] * (m[1][0] * m[2][1] - m[2][0] * m[1][1])
+            }
+            4 => determinant_4(m),
+            _ => match self.lu() {
+                Some((lu, _, sign)) => (0..D).fold(sign, |product, i| product * lu[i][i]),
+                None => T::ZERO,
+            },
         }
-        determinant
     }
 
-    fn minor(&self, row: usize, col: usize) -> f64 {
-        self.submatrix(row, col).determinant()
+    pub fn is_invertible(&self) -> bool {
+        self.determinant() != T::ZERO
     }
 
-    fn cofactor(&self, row: usize, col: usize) -> f64 {
-        let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
-            minor
-        } else {
-            -minor
-        }
+    /// Panics if the matrix isn't invertible, see `try_inverse`.
+    pub fn inverse(&self) -> Self {
+        self.try_inverse()
+            .expect("tried to invert a matrix whose determinant is 0")
     }
 
-    fn submatrix(&self, row: usize, col: usize) -> Matrix<3> {
-        let mut submatrix = Matrix::new();
-        let mut i = 0;
-        let mut j = 0;
+    /// The inverse, or `None` if the determinant is 0. 4x4 matrices use a
+    /// closed form, other sizes solve for every column of the identity with
+    /// the LU decomposition.
+    pub fn try_inverse(&self) -> Option<Self> {
+        if D == 4 {
+            return inverse_4(&self.entries).map(Matrix::from);
+        }
 
-        for (index, element) in self.entries.iter().enumerate() {
-            if index == row {
-                continue;
+        let (lu, permutation, _) = self.lu()?;
+        let mut inverse = Matrix::new();
+        for k in 0..D {
+            let mut x = permutation.map(|row| if row == k { T::ONE } else { T::ZERO });
+            for i in 0..D {
+                for j in 0..i {
+                    x[i] -= lu[i][j] * x[j];
+                }
             }
-            for (index, entry) in element.iter().enumerate() {
-                if index == col {
-                    continue;
+            for i in (0..D).rev() {
+                for j in i + 1..D {
+                    x[i] -= lu[i][j] * x[j];
                 }
-
-                submatrix[i][j] = *entry;
-
-                j = match j {
-                    2 => 0,
-                    _ => j + 1,
-                };
+                x[i] /= lu[i][i];
+            }
+            for i in 0..D {
+                inverse[i][k] = x[i];
             }
-
-            i = match i {
-                2 => 0,
-                _ => i + 1,
-            };
         }
-        submatrix
+        Some(inverse)
     }
+}
 
-    fn is_invertible(&self) -> bool {
-        self.determinant() != 0.0
-    }
+/// The 2x2 determinants formed by pairs of columns of the top two rows and
+/// of the bottom two rows. Both the determinant and the inverse of a 4x4
+/// matrix are built from these, see Laplace expansion along two rows. Only
+/// called with `D` equal to 4, the rows are read by index.
+fn sub_determinants<const D: usize, T: Float>(m: &[[T; D]; D]) -> ([T; 6], [T; 6]) {
+    let pair = |r: usize, i: usize, j: usize| m[r][i] * m[r + 1][j] - m[r + 1][i] * m[r][j];
+    let columns = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+
+    (
+        columns.map(|(i, j)| pair(0, i, j)),
+        columns.map(|(i, j)| pair(2, i, j)),
+    )
+}
 
-    fn inverse(&self) -> Matrix<4> {
-        assert!(self.is_invertible());
+fn expand<T: Float>(s: &[T; 6], c: &[T; 6]) -> T {
+    s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+}
 
-        let mut matrix = Matrix::new();
-        let determinant = self.determinant();
+fn determinant_4<const D: usize, T: Float>(m: &[[T; D]; D]) -> T {
+    let (s, c) = sub_determinants(m);
+    expand(&s, &c)
+}
 
-        for row in 0..4 {
-            for col in 0..4 {
-                let cofactor = self.cofactor(row, col);
-                matrix[col][row] = cofactor / determinant;
-            }
+/// Closed form inverse of a 4x4 matrix through its adjugate.
+fn inverse_4<const D: usize, T: Float>(m: &[[T; D]; D]) -> Option<[[T; D]; D]> {
+    let (s, c) = sub_determinants(m);
+    let determinant = expand(&s, &c);
+    if determinant == T::ZERO {
+        return None;
+    }
+
+    let adjugate = [
+        [
+            m[1][1] * c[5] - m[1][2] * c[4] + m[1][3] * c[3],
+            -m[0][1] * c[5] + m[0][2] * c[4] - m[0][3] * c[3],
+            m[3][1] * s[5] - m[3][2] * s[4] + m[3][3] * s[3],
+            -m[2][1] * s[5] + m[2][2] * s[4] - m[2][3] * s[3],
+        ],
+        [
+            -m[1][0] * c[5] + m[1][2] * c[2] - m[1][3] * c[1],
+            m[0][0] * c[5] - m[0][2] * c[2] + m[0][3] * c[1],
+            -m[3][0] * s[5] + m[3][2] * s[2] - m[3][3] * s[1],
+            m[2][0] * s[5] - m[2][2] * s[2] + m[2][3] * s[1],
+        ],
+        [
+            m[1][0] * c[4] - m[1][1] * c[2] + m[1][3] * c[0],
+            -m[0][0] * c[4] + m[0][1] * c[2] - m[0][3] * c[0],
+            m[3][0] * s[4] - m[3][1] * s[2] + m[3][3] * s[0],
+            -m[2][0] * s[4] + m[2][1] * s[2] - m[2][3] * s[0],
+        ],
+        [
+            -m[1][0] * c[3] + m[1][1] * c[1] - m[1][2] * c[0],
+            m[0][0] * c[3] - m[0][1] * c[1] + m[0][2] * c[0],
+            -m[3][0] * s[3] + m[3][1] * s[1] - m[3][2] * s[0],
+            m[2][0] * s[3] - m[2][1] * s[1] + m[2][2] * s[0],
+        ],
+    ];
+
+    let mut inverse = [[T::ZERO; D]; D];
+    for (row, entries) in adjugate.iter().enumerate() {
+        for (col, entry) in entries.iter().enumerate() {
+            inverse[row][col] = *entry / determinant;
         }
-        matrix
     }
+    Some(inverse)
+}
 
-    fn translation(x: f64, y: f64, z: f64) -> Matrix<4> {
+/// Transformations are built in `f64`, use `cast` for other precisions.
+impl Matrix<4> {
+    pub fn translation(x: f64, y: f64, z: f64) -> Matrix<4> {
         Matrix::from([
             [1.0, 0.0, 0.0, x],
             [0.0, 1.0, 0.0, y],
@@ -179,7 +268,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn scaling(x: f64, y: f64, z: f64) -> Matrix<4> {
+    pub fn scaling(x: f64, y: f64, z: f64) -> Matrix<4> {
         Matrix::from([
             [x, 0.0, 0.0, 0.0],
             [0.0, y, 0.0, 0.0],
@@ -188,7 +277,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn rotation_x(r: f64) -> Matrix<4> {
+    pub fn rotation_x(r: f64) -> Matrix<4> {
         Matrix::from([
             [1.0, 0.0, 0.0, 0.0],
             [0.0, r.cos(), -r.sin(), 0.0],
@@ -197,7 +286,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn rotation_y(r: f64) -> Matrix<4> {
+    pub fn rotation_y(r: f64) -> Matrix<4> {
         Matrix::from([
             [r.cos(), 0.0, r.sin(), 0.0],
             [0.0, 1.0, 0.0, 0.0],
@@ -206,7 +295,7 @@ impl Matrix<4> {
         ])
     }
 
-    fn rotation_z(r: f64) -> Matrix<4> {
+    pub fn rotation_z(r: f64) -> Matrix<4> {
         Matrix::from([
             [r.cos(), -r.sin(), 0.0, 0.0],
             [r.sin(), r.cos(), 0.0, 0.0],
@@ -215,7 +304,40 @@ impl Matrix<4> {
         ])
     }
 
-    fn shearing(x_y: f64, x_z: f64, y_x: f64, y_z: f64, z_x: f64, z_y: f64) -> Matrix<4> {
+    /// Rotates by `r` radians around `axis`, which doesn't need to be
+    /// normalized. Rodrigues' formula, agreeing with `rotation_x` and friends
+    /// for the main axes. A zero axis has no direction and gives the identity.
+    pub fn rotation_axis(axis: Vector, r: f64) -> Matrix<4> {
+        if axis.magnitude() == 0.0 {
+            return Matrix::identity();
+        }
+        let Vector { x, y, z } = axis.normalize();
+        let (sin, cos) = r.sin_cos();
+        let t = 1.0 - cos;
+        Matrix::from([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.0,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.0,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn shearing(x_y: f64, x_z: f64, y_x: f64, y_z: f64, z_x: f64, z_y: f64) -> Matrix<4> {
         Matrix::from([
             [1.0, x_y, x_z, 0.0],
             [y_x, 1.0, y_z, 0.0],
@@ -223,13 +345,79 @@ impl Matrix<4> {
             [0.0, 0.0, 0.0, 1.0],
         ])
     }
+
+    /// Orients the world relative to an eye at `from` looking towards `to`.
+    pub fn view_transform(from: Point, to: Point, up: Vector) -> Matrix<4> {
+        let forward = (to - from).normalize();
+        let left = forward.cross(&up.normalize());
+        let true_up = left.cross(&forward);
+        let orientation = Matrix::from([
+            [left.x, left.y, left.z, 0.0],
+            [true_up.x, true_up.y, true_up.z, 0.0],
+            [-forward.x, -forward.y, -forward.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        orientation * Matrix::translation(-from.x, -from.y, -from.z)
+    }
+
+    /// The same as `view_transform`, by the name other libraries use.
+    pub fn look_at(eye: Point, target: Point, up: Vector) -> Matrix<4> {
+        Matrix::view_transform(eye, target, up)
+    }
+
+    /// Maps the view frustum in front of an eye at the origin, looking down
+    /// -z, to the cube from -1 to 1 once divided by `w`. `field_of_view` is
+    /// vertical, `aspect` is width over height, and z goes from -1 at `near`
+    /// to 1 at `far`.
+    pub fn perspective(field_of_view: f64, aspect: f64, near: f64, far: f64) -> Matrix<4> {
+        let f = 1.0 / (field_of_view / 2.0).tan();
+        Matrix::from([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [
+                0.0,
+                0.0,
+                (far + near) / (near - far),
+                2.0 * far * near / (near - far),
+            ],
+            [0.0, 0.0, -1.0, 0.0],
+        ])
+    }
+
+    /// Maps the box between the given planes to the cube from -1 to 1, with
+    /// the same conventions as `perspective` but without foreshortening.
+    pub fn orthographic(
+        left: f64,
+        right: f64,
+        bottom: f64,
+        top: f64,
+        near: f64,
+        far: f64,
+    ) -> Matrix<4> {
+        Matrix::from([
+            [
+                2.0 / (right - left),
+                0.0,
+                0.0,
+                -(right + left) / (right - left),
+            ],
+            [
+                0.0,
+                2.0 / (top - bottom),
+                0.0,
+                -(top + bottom) / (top - bottom),
+            ],
+            [0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
 }
 
-impl<const D: usize> fmt::Debug for Matrix<D> {
+impl<const D: usize, T: fmt::Display> fmt::Debug for Matrix<D, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let width = 10;
         let precision = 5;
-        writeln!(f, "");
+        writeln!(f)?;
 
         for row in 0..D {
             write!(f, "| {0:>width$}", format!("{0:.precision$}", self[row][0]))?;
@@ -240,41 +428,45 @@ impl<const D: usize> fmt::Debug for Matrix<D> {
     }
 }
 
-impl<const D: usize> ops::Index<usize> for Matrix<D> {
-    type Output = [f64; D];
+impl<const D: usize, T> ops::Index<usize> for Matrix<D, T> {
+    type Output = [T; D];
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.entries[index]
     }
 }
 
-impl<const D: usize> ops::IndexMut<usize> for Matrix<D> {
+impl<const D: usize, T> ops::IndexMut<usize> for Matrix<D, T> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.entries[index]
     }
 }
 
-impl<const D: usize> PartialEq<Self> for Matrix<D> {
-    fn eq(&self, other: &Matrix<D>) -> bool {
+impl<const D: usize, T: Float> PartialEq<Self> for Matrix<D, T> {
+    fn eq(&self, other: &Self) -> bool {
         for row in 0..D {
             for col in 0..D {
-                if equal(self[row][col], other[row][col]) == false {
+                if !self[row][col].is_close(other[row][col]) {
                     return false;
                 }
             }
         }
         true
     }
+}
 
-    fn ne(&self, other: &Matrix<D>) -> bool {
-        !self.eq(other)
+impl<const D: usize, T: Float> ApproxEq for Matrix<D, T> {
+    type Scalar = T;
+
+    fn all_components(&self, other: &Self, eq: impl Fn(T, T) -> bool) -> bool {
+        (0..D).all(|row| (0..D).all(|col| eq(self[row][col], other[row][col])))
     }
 }
 
-impl<const D: usize> ops::Mul<Matrix<D>> for Matrix<D> {
+impl<const D: usize, T: Float> ops::Mul for Matrix<D, T> {
     type Output = Self;
 
-    fn mul(self, other: Matrix<D>) -> Self::Output {
+    fn mul(self, other: Self) -> Self::Output {
         let mut matrix = Matrix::new();
         for row in 0..D {
             for col in 0..D {
@@ -288,11 +480,13 @@ impl<const D: usize> ops::Mul<Matrix<D>> for Matrix<D> {
     }
 }
 
-impl ops::Mul<Point> for Matrix<4> {
-    type Output = Point;
+/// Treats the point as having a `w` of 1 and ignores the bottom row, so the
+/// result of a projection needs a `Tuple4` instead.
+impl<T: Float> ops::Mul<Point<T>> for Matrix<4, T> {
+    type Output = Point<T>;
 
-    fn mul(self, other: Point) -> Self::Output {
-        let mut result = [0.0; 3];
+    fn mul(self, other: Point<T>) -> Self::Output {
+        let mut result = [T::ZERO; 3];
         for row in 0..3 {
             result[row] = self[row][0] * other.x
                 + self[row][1] * other.y
@@ -304,11 +498,12 @@ impl ops::Mul<Point> for Matrix<4> {
     }
 }
 
-impl ops::Mul<Vector> for Matrix<4> {
-    type Output = Vector;
+/// Treats the vector as having a `w` of 0, translations leave it alone.
+impl<T: Float> ops::Mul<Vector<T>> for Matrix<4, T> {
+    type Output = Vector<T>;
 
-    fn mul(self, other: Vector) -> Self::Output {
-        let mut result = [0.0; 3];
+    fn mul(self, other: Vector<T>) -> Self::Output {
+        let mut result = [T::ZERO; 3];
         for row in 0..3 {
             result[row] = self[row][0] * other.x + self[row][1] * other.y + self[row][2] * other.z
         }
@@ -320,6 +515,8 @@ impl ops::Mul<Vector> for Matrix<4> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::equal;
+    use crate::tuple::Tuple4;
     use std::f64::consts::PI;
 
     #[test]
@@ -473,6 +670,19 @@ mod tests {
         assert_eq!(identity_matrix, identity_matrix.transpose());
     }
 
+    #[test]
+    fn should_construct_identity_matrix() {
+        let expected = Matrix::from([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert_eq!(expected, Matrix::identity());
+        assert_eq!(Matrix::from([[1.0, 0.0], [0.0, 1.0]]), Matrix::identity());
+    }
+
     #[test]
     fn should_calculate_the_determinant() {
         let a = Matrix::from([[1.0, 5.0], [-3.0, 2.0]]);
@@ -637,6 +847,47 @@ mod tests {
         assert_eq!(c * b.inverse(), a);
     }
 
+    #[test]
+    fn should_not_try_to_invert_a_singular_matrix() {
+        let a = Matrix::from([
+            [-4.0, 2.0, -2.0, -3.0],
+            [9.0, 6.0, 2.0, 6.0],
+            [0.0, -5.0, 1.0, -5.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]);
+
+        assert!(a.try_inverse().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "determinant is 0")]
+    fn should_panic_when_inverting_a_singular_matrix() {
+        Matrix::<4>::new().inverse();
+    }
+
+    #[test]
+    fn should_match_the_cofactor_expansion() {
+        let a = Matrix::from([
+            [3.0, -9.0, 7.0, 3.0],
+            [3.0, -8.0, 2.0, -9.0],
+            [-4.0, 4.0, 4.0, 1.0],
+            [-6.0, 5.0, -1.0, 1.0],
+        ]);
+
+        let expanded = (0..4)
+            .map(|col| a[0][col] * a.cofactor(0, col))
+            .sum::<f64>();
+        let inverse = a.try_inverse().unwrap();
+
+        assert_eq!(a.determinant(), expanded);
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!(equal(inverse[col][row], a.cofactor(row, col) / expanded));
+            }
+        }
+        assert_eq!(a * inverse, Matrix::identity());
+    }
+
     #[test]
     fn should_multiply_by_a_translation_matrix() {
         let transform = Matrix::translation(5.0, -3.0, 2.0);
@@ -733,6 +984,93 @@ mod tests {
         assert_eq!(full_quarter * p, Point::new(-1.0, 0.0, 0.0));
     }
 
+    #[test]
+    fn should_rotate_a_point_around_any_axis() {
+        let diagonal = Matrix::rotation_axis(Vector::new(1.0, 1.0, 1.0), 2.0 * PI / 3.0);
+
+        assert_eq!(
+            Matrix::rotation_axis(Vector::new(0.0, 3.0, 0.0), 0.8),
+            Matrix::rotation_y(0.8)
+        );
+        assert_eq!(
+            Matrix::rotation_axis(Vector::new(0.0, 0.0, -1.0), 0.8),
+            Matrix::rotation_z(-0.8)
+        );
+        assert_eq!(
+            diagonal * Point::new(1.0, 0.0, 0.0),
+            Point::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            diagonal * Point::new(0.0, 1.0, 0.0),
+            Point::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn should_not_rotate_around_a_zero_axis() {
+        assert_eq!(
+            Matrix::rotation_axis(Vector::new(0.0, 0.0, 0.0), 0.8),
+            Matrix::identity()
+        );
+    }
+
+    #[test]
+    fn should_project_frustum_to_unit_cube() {
+        let projection = Matrix::perspective(PI / 2.0, 2.0, 1.0, 10.0);
+        let project = |point| {
+            (projection * Tuple4::from(point))
+                .perspective_divide()
+                .unwrap()
+        };
+
+        assert_eq!(
+            project(Point::new(0.0, 0.0, -1.0)),
+            Point::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            project(Point::new(0.0, 0.0, -10.0)),
+            Point::new(0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            project(Point::new(2.0, 1.0, -1.0)),
+            Point::new(1.0, 1.0, -1.0)
+        );
+        assert_eq!(
+            project(Point::new(-10.0, -2.5, -5.0)),
+            Point::new(-1.0, -0.5, project(Point::new(0.0, 0.0, -5.0)).z)
+        );
+    }
+
+    #[test]
+    fn should_project_box_to_unit_cube() {
+        let projection = Matrix::orthographic(-2.0, 4.0, -1.0, 1.0, 1.0, 3.0);
+
+        assert_eq!(
+            projection * Point::new(-2.0, -1.0, -1.0),
+            Point::new(-1.0, -1.0, -1.0)
+        );
+        assert_eq!(
+            projection * Point::new(4.0, 1.0, -3.0),
+            Point::new(1.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            projection * Point::new(1.0, 0.0, -2.0),
+            Point::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_look_at_like_view_transform() {
+        let eye = Point::new(1.0, 3.0, 2.0);
+        let target = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(1.0, 1.0, 0.0);
+
+        assert_eq!(
+            Matrix::look_at(eye, target, up),
+            Matrix::view_transform(eye, target, up)
+        );
+    }
+
     #[test]
     fn should_move_x_in_proportion_to_y() {
         let transform = Matrix::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
@@ -803,4 +1141,157 @@ mod tests {
         let t = c * b * a;
         assert_eq!(t * p, Point::new(15.0, 0.0, 7.0));
     }
+
+    #[test]
+    fn should_use_identity_as_default_view_orientation() {
+        let from = Point::new(0.0, 0.0, 0.0);
+        let to = Point::new(0.0, 0.0, -1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        assert_eq!(Matrix::view_transform(from, to, up), Matrix::identity());
+    }
+
+    #[test]
+    fn should_look_in_positive_z_direction() {
+        let from = Point::new(0.0, 0.0, 0.0);
+        let to = Point::new(0.0, 0.0, 1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        assert_eq!(
+            Matrix::view_transform(from, to, up),
+            Matrix::scaling(-1.0, 1.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn should_move_the_world_with_view_transform() {
+        let from = Point::new(0.0, 0.0, 8.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        assert_eq!(
+            Matrix::view_transform(from, to, up),
+            Matrix::translation(0.0, 0.0, -8.0)
+        );
+    }
+
+    #[test]
+    fn should_create_arbitrary_view_transform() {
+        let from = Point::new(1.0, 3.0, 2.0);
+        let to = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(1.0, 1.0, 0.0);
+
+        let expected = Matrix::from([
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.00000],
+            [0.00000, 0.00000, 0.00000, 1.00000],
+        ]);
+
+        assert_eq!(Matrix::view_transform(from, to, up), expected);
+    }
+
+    fn five_by_five() -> Matrix<5> {
+        Matrix::from([
+            [2.0, -1.0, 0.0, 3.0, 1.0],
+            [1.0, 3.0, 2.0, -2.0, 0.0],
+            [0.0, 1.0, 4.0, 1.0, -1.0],
+            [3.0, 0.0, -1.0, 2.0, 2.0],
+            [1.0, 2.0, 1.0, 0.0, 3.0],
+        ])
+    }
+
+    #[test]
+    fn should_calculate_the_determinant_of_larger_matrices() {
+        let b = Matrix::from([
+            [1.0, 2.0, 0.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 3.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 4.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 5.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0, 1.0, 6.0],
+            [7.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert!(equal(five_by_five().determinant(), -2.0));
+        assert!(equal(b.determinant(), -5046.0));
+        assert_eq!(Matrix::<6>::identity().determinant(), 1.0);
+    }
+
+    #[test]
+    fn should_expand_larger_determinants_along_a_row() {
+        let a = five_by_five();
+
+        let expanded = (0..5)
+            .map(|col| a[2][col] * a.cofactor(2, col))
+            .sum::<f64>();
+        let minor = a.submatrix::<4>(2, 3).determinant();
+
+        assert!(equal(expanded, a.determinant()));
+        assert!(equal(a.minor(2, 3), minor));
+        assert!(equal(a.cofactor(2, 3), -minor));
+    }
+
+    #[test]
+    fn should_return_a_4_x_4_submatrix() {
+        let expected = Matrix::from([
+            [2.0, -1.0, 3.0, 1.0],
+            [1.0, 3.0, -2.0, 0.0],
+            [3.0, 0.0, 2.0, 2.0],
+            [1.0, 2.0, 0.0, 3.0],
+        ]);
+
+        assert_eq!(five_by_five().submatrix(2, 2), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "one row and column less")]
+    fn should_reject_submatrix_of_the_wrong_size() {
+        five_by_five().submatrix::<3>(0, 0);
+    }
+
+    #[test]
+    fn should_invert_matrices_of_any_size() {
+        let a = five_by_five();
+        let b = Matrix::from([[4.0, 7.0], [2.0, 6.0]]);
+        let c = Matrix::from([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+
+        assert_eq!(a * a.inverse(), Matrix::identity());
+        assert_eq!(a.inverse() * a, Matrix::identity());
+        assert_eq!(b.inverse(), Matrix::from([[0.6, -0.7], [-0.2, 0.4]]));
+        assert_eq!(c * c.inverse(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_not_invert_singular_matrices_of_any_size() {
+        let mut a = five_by_five();
+        a[4] = a[0];
+
+        assert!(!a.is_invertible());
+        assert!(a.try_inverse().is_none());
+        assert!(Matrix::<3>::new().try_inverse().is_none());
+    }
+
+    #[test]
+    fn should_work_in_single_precision() {
+        let transform = (Matrix::translation(1.0, 2.0, 3.0)
+            * Matrix::rotation_y(0.5)
+            * Matrix::scaling(2.0, 2.0, 2.0))
+        .cast::<f32>();
+        let point = Point::new(1.0_f32, -2.0, 0.5);
+
+        assert_eq!(transform * transform.inverse(), Matrix::identity());
+        assert_eq!(transform.inverse() * (transform * point), point);
+        assert!(transform.determinant().is_close(8.0));
+    }
+
+    #[test]
+    fn should_compare_large_matrices_relatively() {
+        let a = Matrix::translation(4.0e6, 0.0, -2.5e6) * Matrix::rotation_y(PI / 6.0);
+        let b = a.inverse().inverse();
+
+        assert!(a.relative_eq(&b, 1e-12, 1e-12));
+        assert!(a.ulps_eq(&b, 1e-12, 64));
+        assert!(!a.exact_eq(&Matrix::translation(4.0e6, 0.0, -2.5e6)));
+        assert!(a.exact_eq(&a));
+    }
 }
@@ -0,0 +1,233 @@
+use crate::equal;
+use crate::matrix::Matrix;
+use crate::quaternion::Quaternion;
+use crate::vector::Vector;
+use crate::EPSILON;
+
+/// The factors of an affine transform, applied in the order scale, shear,
+/// rotation and translation:
+///
+/// ```text
+/// translation * rotation * shearing(x_y, x_z, 0, y_z, 0, 0) * scaling
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Decomposition {
+    pub translation: Vector,
+    pub rotation: Quaternion,
+    /// Negative along x when the transform mirrors the scene.
+    pub scale: Vector,
+    pub shear: Shear,
+}
+
+/// The arguments of `Matrix::shearing` that move components in
+/// proportion to later ones. The others are always 0 after decomposing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Shear {
+    pub x_y: f64,
+    pub x_z: f64,
+    pub y_z: f64,
+}
+
+impl Decomposition {
+    /// Interpolates every factor on its own, the rotation along the shorter
+    /// way round. Keyframes stay rigid in between instead of the skewing a
+    /// blend of matrices gives.
+    pub fn interpolate(&self, other: &Decomposition, t: f64) -> Decomposition {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        Decomposition {
+            translation: self.translation.lerp(&other.translation, t),
+            rotation: self.rotation.slerp(&other.rotation, t),
+            scale: self.scale.lerp(&other.scale, t),
+            shear: Shear {
+                x_y: lerp(self.shear.x_y, other.shear.x_y),
+                x_z: lerp(self.shear.x_z, other.shear.x_z),
+                y_z: lerp(self.shear.y_z, other.shear.y_z),
+            },
+        }
+    }
+}
+
+impl Matrix<4> {
+    /// Splits an affine transform into its factors, with Gram-Schmidt on the
+    /// columns of the upper left 3x3. Returns `None` for a projection or a
+    /// matrix flattening the scene, which have no such factors.
+    pub fn decompose(&self) -> Option<Decomposition> {
+        let m = self;
+        let affine = equal(m[3][0], 0.0)
+            && equal(m[3][1], 0.0)
+            && equal(m[3][2], 0.0)
+            && equal(m[3][3], 1.0);
+        if !affine {
+            return None;
+        }
+
+        let column = |col: usize| Vector::new(m[0][col], m[1][col], m[2][col]);
+
+        // the volume spanned by the columns is at most the product of their
+        // lengths, comparing the two finds flat transforms at any scale
+        let volume = column(0).cross(&column(1)).dot(&column(2));
+        let lengths = (0..3).map(|col| column(col).magnitude()).product::<f64>();
+        if volume.abs() <= EPSILON * lengths {
+            return None;
+        }
+
+        let mut x_scale = column(0).magnitude();
+        let mut x_axis = column(0) / x_scale;
+
+        let mut x_y = x_axis.dot(&column(1));
+        let y_column = column(1) - x_axis * x_y;
+        let y_scale = y_column.magnitude();
+        let y_axis = y_column / y_scale;
+
+        let mut x_z = x_axis.dot(&column(2));
+        let y_z = y_axis.dot(&column(2));
+        let z_column = column(2) - x_axis * x_z - y_axis * y_z;
+        let z_scale = z_column.magnitude();
+        let z_axis = z_column / z_scale;
+
+        // a mirrored basis isn't a rotation, flip x to make it one
+        if x_axis.cross(&y_axis).dot(&z_axis) < 0.0 {
+            x_axis = -x_axis;
+            x_scale = -x_scale;
+            x_y = -x_y;
+            x_z = -x_z;
+        }
+
+        let rotation = Matrix::from([
+            [x_axis.x, y_axis.x, z_axis.x, 0.0],
+            [x_axis.y, y_axis.y, z_axis.y, 0.0],
+            [x_axis.z, y_axis.z, z_axis.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        Some(Decomposition {
+            translation: Vector::new(m[0][3], m[1][3], m[2][3]),
+            rotation: Quaternion::from_matrix(&rotation),
+            scale: Vector::new(x_scale, y_scale, z_scale),
+            shear: Shear {
+                x_y: x_y / y_scale,
+                x_z: x_z / z_scale,
+                y_z: y_z / z_scale,
+            },
+        })
+    }
+
+    /// The inverse of `decompose`.
+    pub fn compose(decomposition: &Decomposition) -> Matrix<4> {
+        let Decomposition {
+            translation: t,
+            rotation,
+            scale: s,
+            shear,
+        } = decomposition;
+        Matrix::translation(t.x, t.y, t.z)
+            * rotation.to_matrix()
+            * Matrix::shearing(shear.x_y, shear.x_z, 0.0, shear.y_z, 0.0, 0.0)
+            * Matrix::scaling(s.x, s.y, s.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx::ApproxEq;
+    use crate::point::Point;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_decompose_into_factors() {
+        let matrix = Matrix::translation(1.0, -2.0, 3.0)
+            * Matrix::rotation_y(PI / 3.0)
+            * Matrix::scaling(2.0, 3.0, 0.5);
+
+        let decomposition = matrix.decompose().unwrap();
+
+        assert_eq!(decomposition.translation, Vector::new(1.0, -2.0, 3.0));
+        assert_eq!(
+            decomposition.rotation.to_matrix(),
+            Matrix::rotation_y(PI / 3.0)
+        );
+        assert_eq!(decomposition.scale, Vector::new(2.0, 3.0, 0.5));
+        assert_eq!(
+            decomposition.shear,
+            Shear {
+                x_y: 0.0,
+                x_z: 0.0,
+                y_z: 0.0
+            }
+        );
+    }
+
+    #[test]
+    fn should_compose_decomposed_matrices_again() {
+        let matrices = [
+            Matrix::identity(),
+            Matrix::rotation_axis(Vector::new(1.0, 2.0, -1.0), 2.0)
+                * Matrix::shearing(0.5, -1.0, 0.0, 2.0, 0.0, 0.0)
+                * Matrix::scaling(1.5, 0.2, 4.0),
+            Matrix::view_transform(
+                Point::new(0.0, 1.5, -5.0),
+                Point::new(0.0, 1.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            Matrix::shearing(1.0, 2.0, 3.0, 4.0, 5.0, 6.0) * Matrix::translation(1.0, 2.0, 3.0),
+        ];
+
+        for matrix in matrices {
+            let decomposition = matrix.decompose().unwrap();
+            assert_eq!(Matrix::compose(&decomposition), matrix);
+        }
+    }
+
+    #[test]
+    fn should_keep_mirroring_in_the_x_scale() {
+        let matrix = Matrix::rotation_z(0.4) * Matrix::scaling(2.0, -1.0, 1.0);
+
+        let decomposition = matrix.decompose().unwrap();
+
+        assert_eq!(decomposition.scale, Vector::new(-2.0, 1.0, 1.0));
+        assert_eq!(Matrix::compose(&decomposition), matrix);
+    }
+
+    #[test]
+    fn should_not_decompose_projections_or_flat_transforms() {
+        let mut projection = Matrix::identity();
+        projection[3][2] = 1.0;
+
+        assert!(projection.decompose().is_none());
+        assert!(Matrix::scaling(1.0, 0.0, 1.0).decompose().is_none());
+    }
+
+    #[test]
+    fn should_decompose_tiny_scales() {
+        let matrix = Matrix::rotation_x(0.5) * Matrix::scaling(1e-6, 2e-6, 1e-6);
+
+        let decomposition = matrix.decompose().unwrap();
+
+        assert!(decomposition
+            .scale
+            .relative_eq(&Vector::new(1e-6, 2e-6, 1e-6), 0.0, 1e-9));
+        assert!(Matrix::compose(&decomposition).relative_eq(&matrix, 1e-15, 1e-9));
+        assert!(Matrix::scaling(1e-6, 0.0, 1e-6).decompose().is_none());
+    }
+
+    #[test]
+    fn should_interpolate_keyframes() {
+        let start = Matrix::identity();
+        let end = Matrix::translation(4.0, 2.0, 0.0)
+            * Matrix::rotation_z(PI / 2.0)
+            * Matrix::scaling(3.0, 3.0, 3.0);
+
+        let halfway = start
+            .decompose()
+            .unwrap()
+            .interpolate(&end.decompose().unwrap(), 0.5);
+
+        assert_eq!(
+            Matrix::compose(&halfway),
+            Matrix::translation(2.0, 1.0, 0.0)
+                * Matrix::rotation_z(PI / 4.0)
+                * Matrix::scaling(2.0, 2.0, 2.0)
+        );
+    }
+}
@@ -1,22 +1,97 @@
-use crate::equal;
-use crate::vector::Vector;
+use crate::approx::ApproxEq;
+use crate::float::Float;
+use crate::vector::{Axis, Vector};
+use std::iter;
 use std::ops;
 
 #[derive(Debug, Copy, Clone)]
-pub struct Point {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+pub struct Point<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl Point {
-    pub fn new(x: f64, y: f64, z: f64) -> Point {
+impl<T: Float> Point<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
         Point { x, y, z }
     }
+
+    /// Converts to another precision.
+    pub fn cast<U: Float>(&self) -> Point<U> {
+        let convert = |value: T| U::from_f64(value.to_f64());
+        Point::new(convert(self.x), convert(self.y), convert(self.z))
+    }
+
+    /// Goes from this point at `t` = 0 to `other` at `t` = 1.
+    pub fn lerp(&self, other: &Self, t: T) -> Self {
+        *self + (*other - *self) * t
+    }
+
+    pub fn min(&self, other: &Self) -> Self {
+        Self::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    pub fn max(&self, other: &Self) -> Self {
+        Self::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    pub fn distance(&self, other: &Self) -> T {
+        (*self - *other).magnitude()
+    }
+}
+
+impl<T> ops::Index<Axis> for Point<T> {
+    type Output = T;
+
+    fn index(&self, axis: Axis) -> &T {
+        match axis {
+            Axis::X => &self.x,
+            Axis::Y => &self.y,
+            Axis::Z => &self.z,
+        }
+    }
+}
+
+impl<T> ops::IndexMut<Axis> for Point<T> {
+    fn index_mut(&mut self, axis: Axis) -> &mut T {
+        match axis {
+            Axis::X => &mut self.x,
+            Axis::Y => &mut self.y,
+            Axis::Z => &mut self.z,
+        }
+    }
 }
 
-impl From<[f64; 3]> for Point {
-    fn from(array: [f64; 3]) -> Self {
+/// Adds up coordinates as if they were vectors, for averaging points with
+/// `sum / count as f64`.
+impl<T: Float> iter::Sum for Point<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(T::ZERO, T::ZERO, T::ZERO), |sum, point| {
+            Self::new(sum.x + point.x, sum.y + point.y, sum.z + point.z)
+        })
+    }
+}
+
+impl<'a, T: Float> iter::Sum<&'a Point<T>> for Point<T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl<T: Copy> From<[T; 3]> for Point<T> {
+    fn from(array: [T; 3]) -> Self {
         Self {
             x: array[0],
             y: array[1],
@@ -25,16 +100,24 @@ impl From<[f64; 3]> for Point {
     }
 }
 
-impl PartialEq for Point {
+impl<T: Float> PartialEq for Point<T> {
     fn eq(&self, other: &Self) -> bool {
-        equal(self.x, other.x) && equal(self.y, other.y) && equal(self.z, other.z)
+        self.x.is_close(other.x) && self.y.is_close(other.y) && self.z.is_close(other.z)
     }
 }
 
-impl ops::Add<Vector> for Point {
+impl<T: Float> ApproxEq for Point<T> {
+    type Scalar = T;
+
+    fn all_components(&self, other: &Self, eq: impl Fn(T, T) -> bool) -> bool {
+        eq(self.x, other.x) && eq(self.y, other.y) && eq(self.z, other.z)
+    }
+}
+
+impl<T: Float> ops::Add<Vector<T>> for Point<T> {
     type Output = Self;
 
-    fn add(self, other: Vector) -> Self {
+    fn add(self, other: Vector<T>) -> Self {
         Self {
             x: self.x + other.x,
             y: self.y + other.y,
@@ -43,18 +126,18 @@ impl ops::Add<Vector> for Point {
     }
 }
 
-impl ops::Sub for Point {
-    type Output = Vector;
+impl<T: Float> ops::Sub for Point<T> {
+    type Output = Vector<T>;
 
-    fn sub(self, other: Self) -> Vector {
+    fn sub(self, other: Self) -> Vector<T> {
         Vector::new(self.x - other.x, self.y - other.y, self.z - other.z)
     }
 }
 
-impl ops::Sub<Vector> for Point {
+impl<T: Float> ops::Sub<Vector<T>> for Point<T> {
     type Output = Self;
 
-    fn sub(self, other: Vector) -> Self {
+    fn sub(self, other: Vector<T>) -> Self {
         Self {
             x: self.x - other.x,
             y: self.y - other.y,
@@ -63,10 +146,10 @@ impl ops::Sub<Vector> for Point {
     }
 }
 
-impl ops::Mul<f64> for Point {
+impl<T: Float> ops::Mul<T> for Point<T> {
     type Output = Self;
 
-    fn mul(self, scalar: f64) -> Self {
+    fn mul(self, scalar: T) -> Self {
         Self {
             x: self.x * scalar,
             y: self.y * scalar,
@@ -75,10 +158,10 @@ impl ops::Mul<f64> for Point {
     }
 }
 
-impl ops::Div<f64> for Point {
+impl<T: Float> ops::Div<T> for Point<T> {
     type Output = Self;
 
-    fn div(self, scalar: f64) -> Self {
+    fn div(self, scalar: T) -> Self {
         Self {
             x: self.x / scalar,
             y: self.y / scalar,
@@ -87,7 +170,7 @@ impl ops::Div<f64> for Point {
     }
 }
 
-impl ops::Neg for Point {
+impl<T: Float> ops::Neg for Point<T> {
     type Output = Self;
 
     fn neg(self) -> Self {
@@ -173,3 +256,66 @@ fn should_divide_a_point_by_a_scalar() {
 
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn should_interpolate_between_points() {
+    let a = Point::new(0.0, 2.0, -4.0);
+    let b = Point::new(4.0, 2.0, 0.0);
+
+    assert_eq!(a.lerp(&b, 0.5), Point::new(2.0, 2.0, -2.0));
+    assert_eq!(a.lerp(&b, 1.0), b);
+}
+
+#[test]
+fn should_take_component_wise_min_max_and_abs_of_points() {
+    let a = Point::new(1.0, -5.0, 3.0);
+    let b = Point::new(-2.0, 4.0, 3.0);
+
+    assert_eq!(a.min(&b), Point::new(-2.0, -5.0, 3.0));
+    assert_eq!(a.max(&b), Point::new(1.0, 4.0, 3.0));
+    assert_eq!(a.abs(), Point::new(1.0, 5.0, 3.0));
+}
+
+#[test]
+fn should_calculate_distance_between_points() {
+    let a = Point::new(1.0, 2.0, 3.0);
+    let b = Point::new(4.0, 6.0, 3.0);
+
+    assert_eq!(a.distance(&b), 5.0);
+    assert_eq!(b.distance(&a), 5.0);
+}
+
+#[test]
+fn should_index_point_by_axis() {
+    let mut p = Point::new(1.0, 2.0, 3.0);
+    p[Axis::Z] = -1.0;
+
+    assert_eq!(p[Axis::X], 1.0);
+    assert_eq!(p[Axis::Z], -1.0);
+}
+
+#[test]
+fn should_average_points() {
+    let points = [
+        Point::new(1.0, 0.0, 0.0),
+        Point::new(0.0, 1.0, 0.0),
+        Point::new(2.0, 2.0, 3.0),
+    ];
+
+    let centroid = points.iter().sum::<Point>() / points.len() as f64;
+
+    assert_eq!(centroid, Point::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn should_compare_distant_points_relatively() {
+    // 12,000 km from the origin in meters, where neighbouring doubles are
+    // about 2e-9 apart
+    let a = Point::new(1.2e7, -3.0e6, 5.0);
+    let b = Point::new(1.2e7 + 0.01, -3.0e6, 5.0);
+
+    assert_ne!(a, b);
+    assert!(a.relative_eq(&b, 1e-9, 1e-8));
+    assert!(!a.relative_eq(&b, 1e-9, 1e-10));
+    assert!(a.ulps_eq(&Point::new(1.2e7 + 4e-9, -3.0e6, 5.0), 0.0, 4));
+}
@@ -0,0 +1,373 @@
+use crate::equal;
+use crate::matrix::Matrix;
+use crate::vector::Vector;
+use std::ops;
+
+/// A rotation stored as `w + xi + yj + zk`. Only unit quaternions describe
+/// rotations, the constructors here all return one.
+///
+/// Like matrices, `a * b` rotates by `b` first and by `a` afterwards.
+#[derive(Debug, Copy, Clone)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Rotates by `angle` radians around `axis`, counterclockwise when
+    /// looking down the axis towards the origin, like `Matrix::rotation_x`.
+    /// A zero axis gives the identity.
+    pub fn from_axis_angle(axis: Vector, angle: f64) -> Self {
+        if axis.magnitude() == 0.0 {
+            return Self::identity();
+        }
+        let axis = axis.normalize();
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Self::new(cos, axis.x * sin, axis.y * sin, axis.z * sin)
+    }
+
+    /// Rotates around x first, then y, then z, the same as
+    /// `rotation_z(z) * rotation_y(y) * rotation_x(x)`.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Self {
+        Self::from_axis_angle(Vector::new(0.0, 0.0, 1.0), z)
+            * Self::from_axis_angle(Vector::new(0.0, 1.0, 0.0), y)
+            * Self::from_axis_angle(Vector::new(1.0, 0.0, 0.0), x)
+    }
+
+    /// Reads the rotation from the upper left 3x3 of `matrix`, which has to
+    /// be a pure rotation.
+    pub fn from_matrix(matrix: &Matrix<4>) -> Self {
+        let m = matrix;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // divide by the largest of the four components to stay accurate
+        let quaternion = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new(
+                s / 4.0,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                s / 4.0,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                s / 4.0,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Self::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                s / 4.0,
+            )
+        };
+        quaternion.normalize()
+    }
+
+    /// The angles `from_euler` takes, with y between -π/2 and π/2. When y is
+    /// at either end, x and z turn around the same axis and z is 0.
+    pub fn to_euler(self) -> (f64, f64, f64) {
+        let m = self.to_matrix();
+        let y = (-m[2][0]).clamp(-1.0, 1.0).asin();
+        if m[2][0].abs() > 1.0 - 1e-9 {
+            return ((-m[1][2]).atan2(m[1][1]), y, 0.0);
+        }
+        (m[2][1].atan2(m[2][2]), y, m[1][0].atan2(m[0][0]))
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let magnitude = self.magnitude();
+        Self::new(
+            self.w / magnitude,
+            self.x / magnitude,
+            self.y / magnitude,
+            self.z / magnitude,
+        )
+    }
+
+    /// The opposite rotation, for unit quaternions.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    pub fn rotate(&self, vector: Vector) -> Vector {
+        let rotated = *self * Self::new(0.0, vector.x, vector.y, vector.z) * self.conjugate();
+        Vector::new(rotated.x, rotated.y, rotated.z)
+    }
+
+    /// Interpolates at constant angular speed from this rotation at `t` = 0
+    /// to `other` at `t` = 1, along the shorter way round.
+    pub fn slerp(&self, other: &Quaternion, t: f64) -> Self {
+        let mut other = *other;
+        let mut cos = self.dot(&other);
+        // q and -q are the same rotation, but only one of them is close by
+        if cos < 0.0 {
+            other = -other;
+            cos = -cos;
+        }
+
+        // sin(angle) vanishes for nearly equal rotations, where a straight
+        // line is just as good
+        if cos > 0.9995 {
+            return (*self * (1.0 - t) + other * t).normalize();
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+        *self * (((1.0 - t) * angle).sin() / sin) + other * ((t * angle).sin() / sin)
+    }
+
+    pub fn to_matrix(self) -> Matrix<4> {
+        let Quaternion { w, x, y, z } = self.normalize();
+        Matrix::from([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+impl From<Quaternion> for Matrix<4> {
+    fn from(quaternion: Quaternion) -> Self {
+        quaternion.to_matrix()
+    }
+}
+
+/// Compares components, so `q` and `-q` differ even though they describe
+/// the same rotation.
+impl PartialEq for Quaternion {
+    fn eq(&self, other: &Self) -> bool {
+        equal(self.w, other.w)
+            && equal(self.x, other.x)
+            && equal(self.y, other.y)
+            && equal(self.z, other.z)
+    }
+}
+
+impl ops::Mul<Quaternion> for Quaternion {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        }
+    }
+}
+
+impl ops::Mul<f64> for Quaternion {
+    type Output = Self;
+
+    fn mul(self, scalar: f64) -> Self {
+        Self::new(
+            self.w * scalar,
+            self.x * scalar,
+            self.y * scalar,
+            self.z * scalar,
+        )
+    }
+}
+
+impl ops::Add for Quaternion {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.w + other.w,
+            self.x + other.x,
+            self.y + other.y,
+            self.z + other.z,
+        )
+    }
+}
+
+impl ops::Neg for Quaternion {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_match_matrix_rotations_around_the_main_axes() {
+        let x = Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), PI / 3.0);
+        let y = Quaternion::from_axis_angle(Vector::new(0.0, 2.0, 0.0), PI / 3.0);
+        let z = Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), -PI / 5.0);
+
+        assert_eq!(x.to_matrix(), Matrix::rotation_x(PI / 3.0));
+        assert_eq!(y.to_matrix(), Matrix::rotation_y(PI / 3.0));
+        assert_eq!(Matrix::from(z), Matrix::rotation_z(-PI / 5.0));
+    }
+
+    #[test]
+    fn should_match_matrix_rotations_around_any_axis() {
+        let axis = Vector::new(-1.0, 0.5, 2.0);
+
+        assert_eq!(
+            Quaternion::from_axis_angle(axis, 2.5).to_matrix(),
+            Matrix::rotation_axis(axis, 2.5)
+        );
+    }
+
+    #[test]
+    fn should_not_rotate_around_a_zero_axis() {
+        let quaternion = Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 0.0), 2.5);
+
+        assert_eq!(quaternion, Quaternion::identity());
+        assert_eq!(quaternion.to_matrix(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_build_rotation_from_euler_angles() {
+        let quaternion = Quaternion::from_euler(0.3, -1.2, 2.0);
+
+        assert_eq!(
+            quaternion.to_matrix(),
+            Matrix::rotation_z(2.0) * Matrix::rotation_y(-1.2) * Matrix::rotation_x(0.3)
+        );
+    }
+
+    #[test]
+    fn should_read_rotation_from_matrix() {
+        let rotations = [
+            Quaternion::identity(),
+            Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), PI),
+            Quaternion::from_axis_angle(Vector::new(0.0, 1.0, 0.0), 3.0),
+            Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), -3.0),
+            Quaternion::from_axis_angle(Vector::new(2.0, -1.0, 0.5), 1.1),
+        ];
+
+        for rotation in rotations {
+            let read = Quaternion::from_matrix(&rotation.to_matrix());
+            assert!(read == rotation || read == -rotation, "{:?}", rotation);
+        }
+    }
+
+    #[test]
+    fn should_convert_back_to_euler_angles() {
+        let (x, y, z) = Quaternion::from_euler(0.3, -1.2, 2.0).to_euler();
+        assert!(equal(x, 0.3) && equal(y, -1.2) && equal(z, 2.0));
+
+        let gimbal_lock = Quaternion::from_euler(0.3, PI / 2.0, 0.5);
+        let (x, y, z) = gimbal_lock.to_euler();
+        assert_eq!(z, 0.0);
+        assert_eq!(
+            Quaternion::from_euler(x, y, z).to_matrix(),
+            gimbal_lock.to_matrix()
+        );
+    }
+
+    #[test]
+    fn should_compose_like_matrices() {
+        let a = Quaternion::from_axis_angle(Vector::new(1.0, 1.0, 0.0), 0.7);
+        let b = Quaternion::from_axis_angle(Vector::new(0.0, -1.0, 3.0), 2.1);
+
+        assert_eq!((a * b).to_matrix(), a.to_matrix() * b.to_matrix());
+        assert_eq!(a * a.conjugate(), Quaternion::identity());
+    }
+
+    #[test]
+    fn should_rotate_vectors() {
+        let quaternion = Quaternion::from_axis_angle(Vector::new(0.0, 0.0, 1.0), PI / 2.0);
+
+        assert_eq!(
+            quaternion.rotate(Vector::new(1.0, 0.0, 0.0)),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_interpolate_at_constant_speed() {
+        let axis = Vector::new(1.0, 2.0, 3.0);
+        let start = Quaternion::from_axis_angle(axis, 0.2);
+        let end = Quaternion::from_axis_angle(axis, 1.4);
+
+        assert_eq!(start.slerp(&end, 0.0), start);
+        assert_eq!(start.slerp(&end, 1.0), end);
+        assert_eq!(
+            start.slerp(&end, 0.25),
+            Quaternion::from_axis_angle(axis, 0.5)
+        );
+    }
+
+    #[test]
+    fn should_interpolate_along_the_shorter_way() {
+        let axis = Vector::new(0.0, 1.0, 0.0);
+        let start = Quaternion::from_axis_angle(axis, 0.1);
+        let end = -Quaternion::from_axis_angle(axis, 0.5);
+
+        let halfway = start.slerp(&end, 0.5).to_matrix();
+
+        assert_eq!(halfway, Matrix::rotation_y(0.3));
+    }
+
+    #[test]
+    fn should_interpolate_nearly_equal_rotations() {
+        let axis = Vector::new(0.0, 0.0, 1.0);
+        let start = Quaternion::from_axis_angle(axis, 1.0);
+        let end = Quaternion::from_axis_angle(axis, 1.0 + 1e-6);
+
+        let halfway = start.slerp(&end, 0.5);
+
+        assert!(equal(halfway.magnitude(), 1.0));
+        assert_eq!(
+            halfway.to_matrix() * Point::new(1.0, 0.0, 0.0),
+            Point::new(1.0_f64.cos(), 1.0_f64.sin(), 0.0)
+        );
+    }
+}
@@ -0,0 +1,51 @@
+/// Small xorshift64* generator. Rendering only needs cheap, reproducible
+/// noise for sample placement, not statistical quality.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 scrambles neighbouring seeds and guarantees a non-zero state
+        let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        Self { state: z.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    /// Returns a float in 0..1.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[test]
+fn should_produce_floats_in_unit_range() {
+    let mut random = Random::new(0);
+
+    for _ in 0..1000 {
+        let value = random.next_f64();
+        assert!((0.0..1.0).contains(&value));
+    }
+}
+
+#[test]
+fn should_be_reproducible_for_a_seed() {
+    let mut a = Random::new(42);
+    let mut b = Random::new(42);
+    let mut c = Random::new(43);
+
+    let first = a.next_u64();
+
+    assert_eq!(first, b.next_u64());
+    assert_ne!(first, c.next_u64());
+}
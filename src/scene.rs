@@ -0,0 +1,875 @@
+mod json;
+mod value;
+mod yaml;
+
+use crate::camera::{Camera, Region};
+use crate::color::Color;
+use crate::light::{AreaLight, Attenuation, DirectionalLight, Light, PointLight, SpotLight};
+use crate::material::Material;
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::sphere::Sphere;
+use crate::transform::Transform;
+use crate::vector::Vector;
+use crate::world::World;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use value::Value;
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    /// The file is not well formed, `line` counts from 1.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The file is well formed but doesn't describe a valid scene.
+    Invalid(String),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "could not read scene: {}", error),
+            SceneError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            SceneError::Invalid(message) => write!(f, "invalid scene: {}", message),
+            SceneError::UnsupportedFormat(path) => {
+                write!(
+                    f,
+                    "no scene parser for {:?}, expected .yaml, .yml or .json",
+                    path
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(error: io::Error) -> Self {
+        SceneError::Io(error)
+    }
+}
+
+fn invalid<T>(message: impl Into<String>) -> Result<T, SceneError> {
+    Err(SceneError::Invalid(message.into()))
+}
+
+/// Everything needed to render an image.
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub camera: Camera,
+    pub world: World,
+}
+
+impl Scene {
+    /// Reads a scene file, picking the parser from the file extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Scene, SceneError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("yaml") | Some("yml") => Scene::from_yaml(&fs::read_to_string(path)?),
+            Some("json") => Scene::from_json(&fs::read_to_string(path)?),
+            _ => Err(SceneError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// Builds a scene from a list of `add` and `define` entries:
+    ///
+    /// ```yaml
+    /// - define: red
+    ///   value: { color: [ 1, 0, 0 ], specular: 0.2 }
+    /// - define: shiny-red
+    ///   extend: red
+    ///   value: { shininess: 300 }
+    /// - add: camera
+    ///   width: 100
+    ///   height: 50
+    ///   field-of-view: 1.0472
+    ///   from: [ 0, 1.5, -5 ]
+    ///   to: [ 0, 1, 0 ]
+    ///   up: [ 0, 1, 0 ]
+    /// - add: light
+    ///   at: [ -10, 10, -10 ]
+    ///   intensity: [ 1, 1, 1 ]
+    /// - add: sphere
+    ///   material: shiny-red
+    ///   transform:
+    ///     - [ scale, 0.5, 0.5, 0.5 ]
+    ///     - [ translate, 0, 1, 0 ]
+    /// ```
+    ///
+    /// Transforms are applied in the order they are listed.
+    pub fn from_yaml(source: &str) -> Result<Scene, SceneError> {
+        Builder::default().build(&yaml::parse(source)?)
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    defines: HashMap<String, Value>,
+    camera: Option<Camera>,
+    world: World,
+}
+
+impl Builder {
+    fn build(mut self, document: &Value) -> Result<Scene, SceneError> {
+        let entries = match document {
+            Value::Null => &[][..],
+            Value::Array(entries) => entries,
+            _ => return invalid("expected a list of `add` and `define` entries"),
+        };
+
+        for entry in entries {
+            if let Some(name) = entry.get("define") {
+                self.define(name, entry)?;
+            } else if let Some(kind) = entry.get("add") {
+                self.add(kind, entry)?;
+            } else {
+                return invalid("every entry needs an `add` or `define` key");
+            }
+        }
+
+        match self.camera {
+            Some(camera) => Ok(Scene {
+                camera,
+                world: self.world,
+            }),
+            None => invalid("the scene has no camera"),
+        }
+    }
+
+    fn define(&mut self, name: &Value, entry: &Value) -> Result<(), SceneError> {
+        check_keys(entry, &["define", "extend", "value"], "define")?;
+        let name = string(name, "define")?;
+        let value = field(entry, "value", "define")?;
+
+        let value = match entry.get("extend") {
+            None => value.clone(),
+            Some(base) => {
+                let base = string(base, "extend")?;
+                match (self.lookup(base)?, value) {
+                    (Value::Object(base), Value::Object(overrides)) => {
+                        let mut merged = base.clone();
+                        for (key, value) in overrides {
+                            match merged.iter_mut().find(|(name, _)| name == key) {
+                                Some(entry) => entry.1 = value.clone(),
+                                None => merged.push((key.clone(), value.clone())),
+                            }
+                        }
+                        Value::Object(merged)
+                    }
+                    (Value::Array(base), Value::Array(more)) => {
+                        Value::Array(base.iter().chain(more).cloned().collect())
+                    }
+                    _ => return invalid(format!("{:?} can't extend {:?}", name, base)),
+                }
+            }
+        };
+
+        self.defines.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Result<&Value, SceneError> {
+        match self.defines.get(name) {
+            Some(value) => Ok(value),
+            None => invalid(format!("{:?} is not defined", name)),
+        }
+    }
+
+    fn add(&mut self, kind: &Value, entry: &Value) -> Result<(), SceneError> {
+        match string(kind, "add")? {
+            "camera" => self.camera = Some(camera(entry)?),
+            "light" => self.world.lights.push(light(entry)?),
+            "sphere" => {
+                check_keys(entry, &["add", "material", "transform"], "sphere")?;
+                let material = match entry.get("material") {
+                    Some(value) => self.material(value)?,
+                    None => Material::default(),
+                };
+                let transform = match entry.get("transform") {
+                    Some(value) => invertible(self.transform(value)?)?,
+                    None => Transform::identity(),
+                };
+                self.world.objects.push(Sphere::new(transform, material));
+            }
+            other => return invalid(format!("can't add {:?}", other)),
+        }
+        Ok(())
+    }
+
+    fn material(&self, value: &Value) -> Result<Material, SceneError> {
+        let value = match value {
+            Value::String(name) => self.lookup(name)?,
+            value => value,
+        };
+        let entries = match value.as_object() {
+            Some(entries) => entries,
+            None => return invalid("a material must be a mapping or a defined name"),
+        };
+
+        let mut material = Material::default();
+        for (key, value) in entries {
+            match key.as_str() {
+                "color" => material.color = color(value, key)?,
+                "ambient" => material.ambient = number(value, key)?,
+                "diffuse" => material.diffuse = number(value, key)?,
+                "specular" => material.specular = number(value, key)?,
+                "shininess" => material.shininess = number(value, key)?,
+                "reflective" => material.reflective = number(value, key)?,
+                _ => return invalid(format!("unknown material key {:?}", key)),
+            }
+        }
+        Ok(material)
+    }
+
+    /// Composes a list of operations and defined names, each applied after
+    /// the ones listed before it.
+    fn transform(&self, value: &Value) -> Result<Matrix<4>, SceneError> {
+        self.resolve_transform(value, &mut Vec::new())
+    }
+
+    /// Resolves a defined transform. `resolving` holds the names whose
+    /// definitions are being resolved, meeting one of them again means the
+    /// define refers to itself and would never finish.
+    fn named_transform(
+        &self,
+        name: &str,
+        resolving: &mut Vec<String>,
+    ) -> Result<Matrix<4>, SceneError> {
+        if resolving.iter().any(|resolved| resolved == name) {
+            return invalid(format!("define {:?} refers to itself", name));
+        }
+        resolving.push(name.to_string());
+        let transform = self.resolve_transform(self.lookup(name)?, resolving)?;
+        resolving.pop();
+        Ok(transform)
+    }
+
+    fn resolve_transform(
+        &self,
+        value: &Value,
+        resolving: &mut Vec<String>,
+    ) -> Result<Matrix<4>, SceneError> {
+        let operations = match value {
+            Value::String(name) => return self.named_transform(name, resolving),
+            Value::Array(operations) => operations,
+            _ => return invalid("a transform must be a list of operations"),
+        };
+
+        let mut transform = Matrix::identity();
+        for operation in operations {
+            let matrix = match operation {
+                Value::String(name) => self.named_transform(name, resolving)?,
+                Value::Array(items) if !items.is_empty() => {
+                    let name = string(&items[0], "transform")?;
+                    let arguments = items[1..]
+                        .iter()
+                        .map(|item| number(item, name))
+                        .collect::<Result<Vec<f64>, SceneError>>()?;
+                    transform_operation(name, &arguments)?
+                }
+                _ => {
+                    return invalid("a transform operation must be a list like [ scale, 1, 2, 3 ]")
+                }
+            };
+            transform = matrix * transform;
+        }
+        Ok(transform)
+    }
+}
+
+/// Objects keep the inverse of their transform, so it has to exist.
+fn invertible(matrix: Matrix<4>) -> Result<Transform, SceneError> {
+    Transform::try_new(matrix).ok_or_else(|| {
+        SceneError::Invalid("a transform must not flatten an object, like scaling by 0".into())
+    })
+}
+
+fn transform_operation(name: &str, arguments: &[f64]) -> Result<Matrix<4>, SceneError> {
+    let expected = match name {
+        "translate" | "scale" => 3,
+        "rotate-x" | "rotate-y" | "rotate-z" => 1,
+        "shear" => 6,
+        _ => return invalid(format!("unknown transform {:?}", name)),
+    };
+    if arguments.len() != expected {
+        return invalid(format!(
+            "{} takes {} arguments, found {}",
+            name,
+            expected,
+            arguments.len()
+        ));
+    }
+
+    let a = arguments;
+    Ok(match name {
+        "translate" => Matrix::translation(a[0], a[1], a[2]),
+        "scale" => Matrix::scaling(a[0], a[1], a[2]),
+        "rotate-x" => Matrix::rotation_x(a[0]),
+        "rotate-y" => Matrix::rotation_y(a[0]),
+        "rotate-z" => Matrix::rotation_z(a[0]),
+        _ => Matrix::shearing(a[0], a[1], a[2], a[3], a[4], a[5]),
+    })
+}
+
+fn camera(entry: &Value) -> Result<Camera, SceneError> {
+    check_keys(
+        entry,
+        &[
+            "add",
+            "width",
+            "height",
+            "field-of-view",
+            "from",
+            "to",
+            "up",
+            "crop",
+        ],
+        "camera",
+    )?;
+    let width = count(field(entry, "width", "camera")?, "width")?;
+    let height = count(field(entry, "height", "camera")?, "height")?;
+    let field_of_view = number(field(entry, "field-of-view", "camera")?, "field-of-view")?;
+
+    let mut camera = Camera::new(width, height, field_of_view);
+    camera.transform = Matrix::view_transform(
+        point(field(entry, "from", "camera")?, "from")?,
+        point(field(entry, "to", "camera")?, "to")?,
+        vector(field(entry, "up", "camera")?, "up")?,
+    );
+    if let Some(value) = entry.get("crop") {
+        camera.crop = Some(crop(value, &camera)?);
+    }
+    Ok(camera)
+}
+
+/// A point light by default, `type` picks one of the other kinds.
+fn light(entry: &Value) -> Result<Light, SceneError> {
+    let kind = match entry.get("type") {
+        Some(kind) => string(kind, "type")?,
+        None => "point",
+    };
+    let intensity = color(field(entry, "intensity", "light")?, "intensity")?;
+    let attenuation = match entry.get("attenuation") {
+        Some(value) => {
+            let (constant, linear, quadratic) = triple(value, "attenuation")?;
+            Attenuation::new(constant, linear, quadratic)
+        }
+        None => Attenuation::default(),
+    };
+
+    let light = match kind {
+        "point" => {
+            check_keys(
+                entry,
+                &["add", "type", "at", "intensity", "attenuation"],
+                "light",
+            )?;
+            let mut light = PointLight::new(point(field(entry, "at", "light")?, "at")?, intensity);
+            light.attenuation = attenuation;
+            Light::from(light)
+        }
+        "directional" => {
+            check_keys(entry, &["add", "type", "direction", "intensity"], "light")?;
+            let direction = vector(field(entry, "direction", "light")?, "direction")?;
+            Light::from(DirectionalLight::new(direction, intensity))
+        }
+        "spot" => {
+            check_keys(
+                entry,
+                &[
+                    "add",
+                    "type",
+                    "at",
+                    "direction",
+                    "inner-angle",
+                    "outer-angle",
+                    "falloff",
+                    "intensity",
+                    "attenuation",
+                ],
+                "light",
+            )?;
+            let mut light = SpotLight::new(
+                point(field(entry, "at", "light")?, "at")?,
+                vector(field(entry, "direction", "light")?, "direction")?,
+                number(field(entry, "inner-angle", "light")?, "inner-angle")?,
+                number(field(entry, "outer-angle", "light")?, "outer-angle")?,
+                intensity,
+            );
+            if let Some(falloff) = entry.get("falloff") {
+                light.falloff = number(falloff, "falloff")?;
+            }
+            light.attenuation = attenuation;
+            Light::from(light)
+        }
+        "area" => {
+            check_keys(
+                entry,
+                &[
+                    "add",
+                    "type",
+                    "corner",
+                    "uvec",
+                    "usteps",
+                    "vvec",
+                    "vsteps",
+                    "jitter",
+                    "intensity",
+                ],
+                "light",
+            )?;
+            let steps = |key| count(field(entry, key, "light")?, key);
+            let mut light = AreaLight::new(
+                point(field(entry, "corner", "light")?, "corner")?,
+                vector(field(entry, "uvec", "light")?, "uvec")?,
+                steps("usteps")?,
+                vector(field(entry, "vvec", "light")?, "vvec")?,
+                steps("vsteps")?,
+                intensity,
+            );
+            light.jitter = match entry.get("jitter") {
+                Some(Value::Bool(jitter)) => *jitter,
+                Some(_) => return invalid("jitter must be true or false"),
+                None => false,
+            };
+            Light::from(light)
+        }
+        other => return invalid(format!("unknown light type {:?}", other)),
+    };
+    Ok(light)
+}
+
+fn check_keys(entry: &Value, allowed: &[&str], what: &str) -> Result<(), SceneError> {
+    let entries = entry.as_object().unwrap_or_default();
+    match entries
+        .iter()
+        .find(|(key, _)| !allowed.contains(&key.as_str()))
+    {
+        Some((key, _)) => invalid(format!("unknown {} key {:?}", what, key)),
+        None => Ok(()),
+    }
+}
+
+fn field<'a>(entry: &'a Value, key: &str, what: &str) -> Result<&'a Value, SceneError> {
+    match entry.get(key) {
+        Some(value) => Ok(value),
+        None => invalid(format!("{} is missing {:?}", what, key)),
+    }
+}
+
+fn string<'a>(value: &'a Value, what: &str) -> Result<&'a str, SceneError> {
+    match value.as_str() {
+        Some(string) => Ok(string),
+        None => invalid(format!("{} must be a name", what)),
+    }
+}
+
+fn number(value: &Value, what: &str) -> Result<f64, SceneError> {
+    match value.as_f64() {
+        Some(number) => Ok(number),
+        None => invalid(format!("{} must be a number", what)),
+    }
+}
+
+/// A positive whole number, like a size or a sample count.
+fn count(value: &Value, what: &str) -> Result<usize, SceneError> {
+    match value.as_f64() {
+        Some(number) if number >= 1.0 && number.fract() == 0.0 => Ok(number as usize),
+        _ => invalid(format!("{} must be a positive whole number", what)),
+    }
+}
+
+/// `[ x, y, width, height ]` in pixels, covering at least one pixel.
+fn region(value: &Value, what: &str) -> Result<Region, SceneError> {
+    let whole = |value: &Value| match value.as_f64() {
+        Some(number) if number >= 0.0 && number.fract() == 0.0 => Ok(number as usize),
+        _ => invalid(format!("{} must be a list of four whole numbers", what)),
+    };
+    let region = match value.as_array() {
+        Some([x, y, width, height]) => {
+            Region::new(whole(x)?, whole(y)?, whole(width)?, whole(height)?)
+        }
+        _ => return invalid(format!("{} must be a list of four whole numbers", what)),
+    };
+    if region.is_empty() {
+        return invalid(format!("{} must not be empty", what));
+    }
+    Ok(region)
+}
+
+/// A region of the image `camera` renders, which has to overlap it.
+fn crop(value: &Value, camera: &Camera) -> Result<Region, SceneError> {
+    let crop = region(value, "crop")?;
+    let image = Region::new(0, 0, camera.hsize, camera.vsize);
+    if crop.intersect(&image).is_empty() {
+        return invalid(format!(
+            "crop lies outside the {}x{} image",
+            camera.hsize, camera.vsize
+        ));
+    }
+    Ok(crop)
+}
+
+fn triple(value: &Value, what: &str) -> Result<(f64, f64, f64), SceneError> {
+    match value.as_array() {
+        Some([x, y, z]) => Ok((number(x, what)?, number(y, what)?, number(z, what)?)),
+        _ => invalid(format!("{} must be a list of three numbers", what)),
+    }
+}
+
+fn point(value: &Value, what: &str) -> Result<Point, SceneError> {
+    let (x, y, z) = triple(value, what)?;
+    Ok(Point::new(x, y, z))
+}
+
+fn vector(value: &Value, what: &str) -> Result<Vector, SceneError> {
+    let (x, y, z) = triple(value, what)?;
+    Ok(Vector::new(x, y, z))
+}
+
+fn color(value: &Value, what: &str) -> Result<Color, SceneError> {
+    let (r, g, b) = triple(value, what)?;
+    Ok(Color::new(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const CAMERA: &str = r"
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [ -6, 6, -10 ]
+  to: [ 6, 0, 6 ]
+  up: [ -0.45, 1, 0 ]
+";
+
+    fn load(entries: &str) -> Scene {
+        Scene::from_yaml(&format!("{}{}", CAMERA, entries)).unwrap()
+    }
+
+    fn error(entries: &str) -> SceneError {
+        Scene::from_yaml(&format!("{}{}", CAMERA, entries)).unwrap_err()
+    }
+
+    #[test]
+    fn should_load_camera() {
+        let scene = load("");
+
+        assert_eq!(scene.camera.hsize, 100);
+        assert_eq!(scene.camera.vsize, 50);
+        assert_eq!(scene.camera.field_of_view, 0.785);
+        assert_eq!(
+            scene.camera.transform,
+            Matrix::view_transform(
+                Point::new(-6.0, 6.0, -10.0),
+                Point::new(6.0, 0.0, 6.0),
+                Vector::new(-0.45, 1.0, 0.0)
+            )
+        );
+        assert!(scene.world.objects.is_empty());
+        assert_eq!(scene.camera.crop, None);
+    }
+
+    #[test]
+    fn should_load_camera_crop() {
+        let scene = Scene::from_yaml(&format!("{}  crop: [ 10, 0, 20, 5 ]\n", CAMERA)).unwrap();
+
+        assert_eq!(scene.camera.crop, Some(Region::new(10, 0, 20, 5)));
+    }
+
+    #[test]
+    fn should_reject_crops_without_pixels() {
+        let crop = |region: &str| {
+            Scene::from_yaml(&format!("{}  crop: {}\n", CAMERA, region))
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            crop("[ 10, 0, 0, 5 ]"),
+            "invalid scene: crop must not be empty"
+        );
+        assert_eq!(
+            crop("[ 100, 0, 20, 5 ]"),
+            "invalid scene: crop lies outside the 100x50 image"
+        );
+    }
+
+    #[test]
+    fn should_require_a_camera() {
+        let error = Scene::from_yaml("- add: sphere\n").unwrap_err();
+
+        assert!(matches!(error, SceneError::Invalid(_)));
+    }
+
+    #[test]
+    fn should_load_lights() {
+        let scene = load(
+            r"
+- add: light
+  at: [ 50, 100, -50 ]
+  intensity: [ 1, 1, 1 ]
+- add: light
+  type: directional
+  direction: [ 0, -1, 0 ]
+  intensity: [ 0.2, 0.2, 0.2 ]
+- add: light
+  type: spot
+  at: [ 0, 10, 0 ]
+  direction: [ 0, -1, 0 ]
+  inner-angle: 0.2
+  outer-angle: 0.4
+  falloff: 2
+  intensity: [ 1, 1, 1 ]
+  attenuation: [ 1, 0, 0.01 ]
+- add: light
+  type: area
+  corner: [ -1, 2, 4 ]
+  uvec: [ 2, 0, 0 ]
+  usteps: 4
+  vvec: [ 0, 2, 0 ]
+  vsteps: 2
+  jitter: true
+  intensity: [ 1.5, 1.5, 1.5 ]
+",
+        );
+
+        let white = Color::new(1.0, 1.0, 1.0);
+        let mut spot = SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.0, -1.0, 0.0),
+            0.2,
+            0.4,
+            white,
+        );
+        spot.falloff = 2.0;
+        spot.attenuation = Attenuation::new(1.0, 0.0, 0.01);
+        let mut area = AreaLight::new(
+            Point::new(-1.0, 2.0, 4.0),
+            Vector::new(2.0, 0.0, 0.0),
+            4,
+            Vector::new(0.0, 2.0, 0.0),
+            2,
+            Color::new(1.5, 1.5, 1.5),
+        );
+        area.jitter = true;
+
+        assert_eq!(
+            scene.world.lights,
+            vec![
+                Light::from(PointLight::new(Point::new(50.0, 100.0, -50.0), white)),
+                Light::from(DirectionalLight::new(
+                    Vector::new(0.0, -1.0, 0.0),
+                    Color::new(0.2, 0.2, 0.2)
+                )),
+                Light::from(spot),
+                Light::from(area),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_load_sphere_with_inline_material() {
+        let scene = load(
+            r"
+- add: sphere
+  material:
+    color: [ 1, 0.2, 1 ]
+    diffuse: 0.1
+    shininess: 50
+    reflective: 0.5
+",
+        );
+
+        assert_eq!(
+            scene.world.objects[0].material,
+            Material {
+                color: Color::new(1.0, 0.2, 1.0),
+                diffuse: 0.1,
+                shininess: 50.0,
+                reflective: 0.5,
+                ..Material::default()
+            }
+        );
+        assert_eq!(scene.world.objects[0].transform, Matrix::identity());
+    }
+
+    #[test]
+    fn should_extend_defined_materials() {
+        let scene = load(
+            r"
+- define: white-material
+  value:
+    color: [ 1, 1, 1 ]
+    diffuse: 0.7
+- define: blue-material
+  extend: white-material
+  value:
+    color: [ 0.537, 0.831, 0.914 ]
+- add: sphere
+  material: blue-material
+",
+        );
+
+        assert_eq!(
+            scene.world.objects[0].material,
+            Material {
+                color: Color::new(0.537, 0.831, 0.914),
+                diffuse: 0.7,
+                ..Material::default()
+            }
+        );
+    }
+
+    #[test]
+    fn should_apply_transforms_in_listed_order() {
+        let scene = load(
+            r"
+- define: standard-transform
+  value:
+    - [ translate, 1, -1, 1 ]
+    - [ scale, 0.5, 0.5, 0.5 ]
+- add: sphere
+  transform:
+    - standard-transform
+    - [ rotate-x, 1.5707963267948966 ]
+    - [ shear, 1, 0, 0, 0, 0, 0 ]
+",
+        );
+
+        let expected = Matrix::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            * Matrix::rotation_x(PI / 2.0)
+            * Matrix::scaling(0.5, 0.5, 0.5)
+            * Matrix::translation(1.0, -1.0, 1.0);
+        assert_eq!(scene.world.objects[0].transform, expected);
+    }
+
+    #[test]
+    fn should_reject_unknown_names() {
+        assert!(matches!(error("- add: cube\n"), SceneError::Invalid(_)));
+        assert!(matches!(
+            error("- add: sphere\n  material: missing\n"),
+            SceneError::Invalid(_)
+        ));
+        assert!(matches!(
+            error("- add: sphere\n  transform:\n    - [ spin, 1 ]\n"),
+            SceneError::Invalid(_)
+        ));
+        assert!(matches!(
+            error("- add: sphere\n  colour: [ 1, 0, 0 ]\n"),
+            SceneError::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn should_reject_wrong_argument_counts() {
+        let error = error("- add: sphere\n  transform:\n    - [ translate, 1, 2 ]\n");
+
+        assert_eq!(
+            error.to_string(),
+            "invalid scene: translate takes 3 arguments, found 2"
+        );
+    }
+
+    #[test]
+    fn should_reject_defines_referring_to_themselves() {
+        let direct = error(
+            "- define: spin
+  value:
+    - [ rotate-y, 1 ]
+    - spin
+- add: sphere
+  transform: spin
+",
+        );
+        let indirect = error(
+            "- define: a
+  value: [ b ]
+- define: b
+  value: [ [ scale, 2, 2, 2 ], a ]
+- add: sphere
+  transform: [ a ]
+",
+        );
+
+        assert_eq!(
+            direct.to_string(),
+            "invalid scene: define \"spin\" refers to itself"
+        );
+        assert_eq!(
+            indirect.to_string(),
+            "invalid scene: define \"a\" refers to itself"
+        );
+    }
+
+    #[test]
+    fn should_reject_transforms_that_cannot_be_inverted() {
+        let error = error("- add: sphere\n  transform:\n    - [ scale, 1, 0, 1 ]\n");
+
+        assert!(matches!(error, SceneError::Invalid(_)));
+    }
+
+    #[test]
+    fn should_reject_area_lights_without_steps() {
+        let error = error(
+            "- add: light
+  type: area
+  corner: [ 0, 0, 0 ]
+  uvec: [ 1, 0, 0 ]
+  usteps: 0
+  vvec: [ 0, 1, 0 ]
+  vsteps: 1
+  intensity: [ 1, 1, 1 ]
+",
+        );
+
+        assert_eq!(
+            error.to_string(),
+            "invalid scene: usteps must be a positive whole number"
+        );
+    }
+
+    #[test]
+    fn should_pick_parser_from_extension() {
+        let error = Scene::load("scene.txt").unwrap_err();
+
+        assert!(matches!(error, SceneError::UnsupportedFormat(_)));
+    }
+
+    #[test]
+    fn should_load_scene_file() {
+        let path = std::env::temp_dir().join("should_load_scene_file.yml");
+        fs::write(&path, CAMERA).unwrap();
+
+        let scene = Scene::load(&path).unwrap();
+
+        assert_eq!(scene.camera.hsize, 100);
+        fs::remove_file(path).unwrap();
+    }
+}
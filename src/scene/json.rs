@@ -0,0 +1,964 @@
+//! JSON form of a scene. Unlike the YAML format, which describes a scene the
+//! way a person would write it, this stores the scene exactly as it is held
+//! in memory: transforms are full matrices and every number is written with
+//! enough digits to read back the same `f64`.
+
+use crate::camera::{Camera, Sampling};
+use crate::color::Color;
+use crate::light::{AreaLight, Attenuation, DirectionalLight, Light, PointLight, SpotLight};
+use crate::material::Material;
+use crate::matrix::Matrix;
+use crate::scene::value::Value;
+use crate::scene::{
+    check_keys, color, count, crop, field, invalid, invertible, number, point, string, triple,
+    vector, Scene, SceneError,
+};
+use crate::sphere::Sphere;
+use crate::world::World;
+
+use std::fmt::Write;
+
+impl Scene {
+    /// Serializes the scene as pretty printed JSON:
+    ///
+    /// ```json
+    /// {
+    ///   "camera": { "width": 100, "height": 50, "field-of-view": 1.0472, "transform": [..], "sampling": {..} },
+    ///   "lights": [ { "type": "point", "position": [..], "intensity": [..], "attenuation": [..] } ],
+    ///   "objects": [ { "type": "sphere", "transform": [..], "material": {..} } ]
+    /// }
+    /// ```
+    ///
+    /// Area lights store the `uvec` and `vvec` of a single cell, as
+    /// `AreaLight` does, rather than the full edges the YAML format takes.
+    /// Fails if the scene holds an infinite or NaN number, which JSON can't
+    /// represent.
+    pub fn to_json(&self) -> Result<String, SceneError> {
+        let document = Value::Object(vec![
+            ("camera".to_string(), camera_to_value(&self.camera)),
+            (
+                "lights".to_string(),
+                Value::Array(self.world.lights.iter().map(light_to_value).collect()),
+            ),
+            (
+                "objects".to_string(),
+                Value::Array(self.world.objects.iter().map(sphere_to_value).collect()),
+            ),
+        ]);
+
+        let mut json = String::new();
+        write_value(&mut json, &document, 0)?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    pub fn from_json(source: &str) -> Result<Scene, SceneError> {
+        let document = parse(source)?;
+        check_keys(&document, &["camera", "lights", "objects"], "scene")?;
+
+        let camera = camera_from_value(field(&document, "camera", "scene")?)?;
+        let mut world = World::new();
+        if let Some(lights) = document.get("lights") {
+            world.lights = list(lights, "lights")?
+                .iter()
+                .map(light_from_value)
+                .collect::<Result<Vec<Light>, SceneError>>()?;
+        }
+        if let Some(objects) = document.get("objects") {
+            world.objects = list(objects, "objects")?
+                .iter()
+                .map(sphere_from_value)
+                .collect::<Result<Vec<Sphere>, SceneError>>()?;
+        }
+
+        Ok(Scene { camera, world })
+    }
+}
+
+fn entry(key: &str, value: Value) -> (String, Value) {
+    (key.to_string(), value)
+}
+
+fn name(name: &str) -> Value {
+    Value::String(name.to_string())
+}
+
+fn numbers(numbers: &[f64]) -> Value {
+    Value::Array(numbers.iter().map(|n| Value::Number(*n)).collect())
+}
+
+fn rgb(color: Color) -> Value {
+    numbers(&[color.r, color.g, color.b])
+}
+
+fn matrix_to_value(matrix: &Matrix<4>) -> Value {
+    Value::Array((0..4).map(|row| numbers(&matrix[row])).collect())
+}
+
+fn camera_to_value(camera: &Camera) -> Value {
+    let sampling = match camera.sampling {
+        Sampling::Grid { n } => vec![
+            entry("type", name("grid")),
+            entry("n", Value::Number(n as f64)),
+        ],
+        Sampling::Jittered { n } => vec![
+            entry("type", name("jittered")),
+            entry("n", Value::Number(n as f64)),
+        ],
+        Sampling::Adaptive { n, threshold } => vec![
+            entry("type", name("adaptive")),
+            entry("n", Value::Number(n as f64)),
+            entry("threshold", Value::Number(threshold)),
+        ],
+    };
+
+    let mut entries = vec![
+        entry("width", Value::Number(camera.hsize as f64)),
+        entry("height", Value::Number(camera.vsize as f64)),
+        entry("field-of-view", Value::Number(camera.field_of_view)),
+        entry("transform", matrix_to_value(&camera.transform)),
+        entry("sampling", Value::Object(sampling)),
+    ];
+    if let Some(crop) = camera.crop {
+        let region = [crop.x, crop.y, crop.width, crop.height];
+        entries.push(entry("crop", numbers(&region.map(|n| n as f64))));
+    }
+    Value::Object(entries)
+}
+
+fn attenuation_to_value(attenuation: &Attenuation) -> Value {
+    numbers(&[
+        attenuation.constant,
+        attenuation.linear,
+        attenuation.quadratic,
+    ])
+}
+
+fn light_to_value(light: &Light) -> Value {
+    let entries = match light {
+        Light::Point(light) => vec![
+            entry("type", name("point")),
+            entry(
+                "position",
+                numbers(&[light.position.x, light.position.y, light.position.z]),
+            ),
+            entry("intensity", rgb(light.intensity)),
+            entry("attenuation", attenuation_to_value(&light.attenuation)),
+        ],
+        Light::Directional(light) => vec![
+            entry("type", name("directional")),
+            entry(
+                "direction",
+                numbers(&[light.direction.x, light.direction.y, light.direction.z]),
+            ),
+            entry("intensity", rgb(light.intensity)),
+        ],
+        Light::Spot(light) => vec![
+            entry("type", name("spot")),
+            entry(
+                "position",
+                numbers(&[light.position.x, light.position.y, light.position.z]),
+            ),
+            entry(
+                "direction",
+                numbers(&[light.direction.x, light.direction.y, light.direction.z]),
+            ),
+            entry("inner-angle", Value::Number(light.inner_angle)),
+            entry("outer-angle", Value::Number(light.outer_angle)),
+            entry("falloff", Value::Number(light.falloff)),
+            entry("intensity", rgb(light.intensity)),
+            entry("attenuation", attenuation_to_value(&light.attenuation)),
+        ],
+        Light::Area(light) => vec![
+            entry("type", name("area")),
+            entry(
+                "corner",
+                numbers(&[light.corner.x, light.corner.y, light.corner.z]),
+            ),
+            entry("uvec", numbers(&[light.uvec.x, light.uvec.y, light.uvec.z])),
+            entry("usteps", Value::Number(light.usteps as f64)),
+            entry("vvec", numbers(&[light.vvec.x, light.vvec.y, light.vvec.z])),
+            entry("vsteps", Value::Number(light.vsteps as f64)),
+            entry("jitter", Value::Bool(light.jitter)),
+            entry("intensity", rgb(light.intensity)),
+        ],
+    };
+    Value::Object(entries)
+}
+
+fn sphere_to_value(sphere: &Sphere) -> Value {
+    let material = &sphere.material;
+    Value::Object(vec![
+        entry("type", name("sphere")),
+        entry("transform", matrix_to_value(&sphere.transform.matrix())),
+        entry(
+            "material",
+            Value::Object(vec![
+                entry("color", rgb(material.color)),
+                entry("ambient", Value::Number(material.ambient)),
+                entry("diffuse", Value::Number(material.diffuse)),
+                entry("specular", Value::Number(material.specular)),
+                entry("shininess", Value::Number(material.shininess)),
+                entry("reflective", Value::Number(material.reflective)),
+            ]),
+        ),
+    ])
+}
+
+fn list<'a>(value: &'a Value, what: &str) -> Result<&'a [Value], SceneError> {
+    match value.as_array() {
+        Some(items) => Ok(items),
+        None => invalid(format!("{} must be a list", what)),
+    }
+}
+
+fn matrix_from_value(value: &Value, what: &str) -> Result<Matrix<4>, SceneError> {
+    let rows = list(value, what)?;
+    if rows.len() != 4 {
+        return invalid(format!("{} must be a list of four rows", what));
+    }
+
+    let mut matrix = Matrix::new();
+    for (row, entries) in rows.iter().enumerate() {
+        match entries.as_array() {
+            Some(entries) if entries.len() == 4 => {
+                for (col, entry) in entries.iter().enumerate() {
+                    matrix[row][col] = number(entry, what)?;
+                }
+            }
+            _ => return invalid(format!("{} rows must be lists of four numbers", what)),
+        }
+    }
+    Ok(matrix)
+}
+
+fn camera_from_value(value: &Value) -> Result<Camera, SceneError> {
+    check_keys(
+        value,
+        &[
+            "width",
+            "height",
+            "field-of-view",
+            "transform",
+            "sampling",
+            "crop",
+        ],
+        "camera",
+    )?;
+    let mut camera = Camera::new(
+        count(field(value, "width", "camera")?, "width")?,
+        count(field(value, "height", "camera")?, "height")?,
+        number(field(value, "field-of-view", "camera")?, "field-of-view")?,
+    );
+    camera.transform = matrix_from_value(field(value, "transform", "camera")?, "transform")?;
+    if let Some(value) = value.get("crop") {
+        camera.crop = Some(crop(value, &camera)?);
+    }
+
+    if let Some(sampling) = value.get("sampling") {
+        let n = count(field(sampling, "n", "sampling")?, "n")?;
+        camera.sampling = match string(field(sampling, "type", "sampling")?, "type")? {
+            "grid" => {
+                check_keys(sampling, &["type", "n"], "sampling")?;
+                Sampling::Grid { n }
+            }
+            "jittered" => {
+                check_keys(sampling, &["type", "n"], "sampling")?;
+                Sampling::Jittered { n }
+            }
+            "adaptive" => {
+                check_keys(sampling, &["type", "n", "threshold"], "sampling")?;
+                let threshold = number(field(sampling, "threshold", "sampling")?, "threshold")?;
+                if threshold.is_nan() || threshold < 0.0 {
+                    return invalid("threshold must not be negative".to_string());
+                }
+                Sampling::Adaptive { n, threshold }
+            }
+            other => return invalid(format!("unknown sampling type {:?}", other)),
+        };
+    }
+    Ok(camera)
+}
+
+fn attenuation_from_value(value: &Value) -> Result<Attenuation, SceneError> {
+    let (constant, linear, quadratic) = triple(value, "attenuation")?;
+    Ok(Attenuation::new(constant, linear, quadratic))
+}
+
+/// Fills the light structs directly, the constructors would normalize
+/// directions and divide area light edges again.
+fn light_from_value(value: &Value) -> Result<Light, SceneError> {
+    let get = |key| field(value, key, "light");
+    let intensity = color(get("intensity")?, "intensity")?;
+
+    let light = match string(get("type")?, "type")? {
+        "point" => {
+            check_keys(
+                value,
+                &["type", "position", "intensity", "attenuation"],
+                "light",
+            )?;
+            Light::from(PointLight {
+                position: point(get("position")?, "position")?,
+                intensity,
+                attenuation: attenuation_from_value(get("attenuation")?)?,
+            })
+        }
+        "directional" => {
+            check_keys(value, &["type", "direction", "intensity"], "light")?;
+            Light::from(DirectionalLight {
+                direction: vector(get("direction")?, "direction")?,
+                intensity,
+            })
+        }
+        "spot" => {
+            check_keys(
+                value,
+                &[
+                    "type",
+                    "position",
+                    "direction",
+                    "inner-angle",
+                    "outer-angle",
+                    "falloff",
+                    "intensity",
+                    "attenuation",
+                ],
+                "light",
+            )?;
+            Light::from(SpotLight {
+                position: point(get("position")?, "position")?,
+                direction: vector(get("direction")?, "direction")?,
+                inner_angle: number(get("inner-angle")?, "inner-angle")?,
+                outer_angle: number(get("outer-angle")?, "outer-angle")?,
+                falloff: number(get("falloff")?, "falloff")?,
+                intensity,
+                attenuation: attenuation_from_value(get("attenuation")?)?,
+            })
+        }
+        "area" => {
+            check_keys(
+                value,
+                &[
+                    "type",
+                    "corner",
+                    "uvec",
+                    "usteps",
+                    "vvec",
+                    "vsteps",
+                    "jitter",
+                    "intensity",
+                ],
+                "light",
+            )?;
+            Light::from(AreaLight {
+                corner: point(get("corner")?, "corner")?,
+                uvec: vector(get("uvec")?, "uvec")?,
+                usteps: count(get("usteps")?, "usteps")?,
+                vvec: vector(get("vvec")?, "vvec")?,
+                vsteps: count(get("vsteps")?, "vsteps")?,
+                intensity,
+                jitter: match get("jitter")? {
+                    Value::Bool(jitter) => *jitter,
+                    _ => return invalid("jitter must be true or false"),
+                },
+            })
+        }
+        other => return invalid(format!("unknown light type {:?}", other)),
+    };
+    Ok(light)
+}
+
+fn sphere_from_value(value: &Value) -> Result<Sphere, SceneError> {
+    check_keys(value, &["type", "transform", "material"], "object")?;
+    match string(field(value, "type", "object")?, "type")? {
+        "sphere" => {}
+        other => return invalid(format!("unknown object type {:?}", other)),
+    }
+
+    let material = field(value, "material", "sphere")?;
+    check_keys(
+        material,
+        &[
+            "color",
+            "ambient",
+            "diffuse",
+            "specular",
+            "shininess",
+            "reflective",
+        ],
+        "material",
+    )?;
+    let get = |key| field(material, key, "material");
+
+    Ok(Sphere::new(
+        invertible(matrix_from_value(
+            field(value, "transform", "sphere")?,
+            "transform",
+        )?)?,
+        Material {
+            color: color(get("color")?, "color")?,
+            ambient: number(get("ambient")?, "ambient")?,
+            diffuse: number(get("diffuse")?, "diffuse")?,
+            specular: number(get("specular")?, "specular")?,
+            shininess: number(get("shininess")?, "shininess")?,
+            // optional, scenes written before reflections don't have it
+            reflective: match material.get("reflective") {
+                Some(reflective) => number(reflective, "reflective")?,
+                None => Material::default().reflective,
+            },
+        },
+    ))
+}
+
+/// Writes `value` indented by `depth` levels. Lists of plain values, like
+/// vectors and matrix rows, stay on one line so scenes diff line by line.
+fn write_value(json: &mut String, value: &Value, depth: usize) -> Result<(), SceneError> {
+    let indent = "  ".repeat(depth + 1);
+    match value {
+        Value::Null => json.push_str("null"),
+        Value::Bool(bool) => json.push_str(if *bool { "true" } else { "false" }),
+        // `Display` prints the shortest digits that parse back to the same
+        // `f64`; JSON has no way to write infinities or NaN
+        Value::Number(number) if number.is_finite() => write!(json, "{}", number).unwrap(),
+        Value::Number(number) => {
+            return invalid(format!("can't write {} as a JSON number", number))
+        }
+        Value::String(string) => write_string(json, string),
+        Value::Array(items) if items.is_empty() => json.push_str("[]"),
+        Value::Array(items) if items.iter().all(|item| !is_container(item)) => {
+            json.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    json.push_str(", ");
+                }
+                write_value(json, item, depth + 1)?;
+            }
+            json.push(']');
+        }
+        Value::Array(items) => {
+            json.push_str("[\n");
+            for (index, item) in items.iter().enumerate() {
+                json.push_str(&indent);
+                write_value(json, item, depth + 1)?;
+                json.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            json.push_str(&indent[2..]);
+            json.push(']');
+        }
+        Value::Object(entries) if entries.is_empty() => json.push_str("{}"),
+        Value::Object(entries) => {
+            json.push_str("{\n");
+            for (index, (key, item)) in entries.iter().enumerate() {
+                json.push_str(&indent);
+                write_string(json, key);
+                json.push_str(": ");
+                write_value(json, item, depth + 1)?;
+                json.push_str(if index + 1 < entries.len() {
+                    ",\n"
+                } else {
+                    "\n"
+                });
+            }
+            json.push_str(&indent[2..]);
+            json.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn is_container(value: &Value) -> bool {
+    matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+fn write_string(json: &mut String, string: &str) {
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// How deeply arrays and objects may nest. Scenes need a handful of levels,
+/// the limit keeps hostile input from overflowing the stack.
+const MAX_NESTING: usize = 128;
+
+struct Parser<'a> {
+    source: &'a [u8],
+    position: usize,
+    /// The number of arrays and objects the parser is inside of.
+    nesting: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, SceneError> {
+        let line = 1 + self.source[..self.position]
+            .iter()
+            .filter(|byte| **byte == b'\n')
+            .count();
+        Err(SceneError::Syntax {
+            line,
+            message: message.into(),
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.source.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), SceneError> {
+        self.skip_whitespace();
+        if self.source.get(self.position) == Some(&byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", byte as char))
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, SceneError> {
+        if self.source[self.position..].starts_with(literal.as_bytes()) {
+            self.position += literal.len();
+            Ok(value)
+        } else {
+            self.error("expected a value")
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, SceneError> {
+        self.skip_whitespace();
+        match self.source.get(self.position) {
+            Some(b'{' | b'[') if self.nesting == MAX_NESTING => {
+                self.error(format!("nested deeper than {} levels", MAX_NESTING))
+            }
+            Some(b'{') => self.nested(Parser::object),
+            Some(b'[') => self.nested(Parser::array),
+            Some(b'"') => Ok(Value::String(self.string()?)),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => self.error("expected a value"),
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Value, SceneError>,
+    ) -> Result<Value, SceneError> {
+        self.nesting += 1;
+        let value = parse(self);
+        self.nesting -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<Value, SceneError> {
+        self.expect(b'{')?;
+        let mut entries: Vec<(String, Value)> = Vec::new();
+        self.skip_whitespace();
+        if self.source.get(self.position) == Some(&b'}') {
+            self.position += 1;
+            return Ok(Value::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.source.get(self.position) != Some(&b'"') {
+                return self.error("expected a string key");
+            }
+            let key = self.string()?;
+            if entries.iter().any(|(existing, _)| *existing == key) {
+                return self.error(format!("duplicate key {:?}", key));
+            }
+            self.expect(b':')?;
+            let value = self.value()?;
+            entries.push((key, value));
+
+            self.skip_whitespace();
+            match self.source.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Value::Object(entries));
+                }
+                _ => return self.error("expected `,` or `}` in object"),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, SceneError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.source.get(self.position) == Some(&b']') {
+            self.position += 1;
+            return Ok(Value::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.source.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return self.error("expected `,` or `]` in array"),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, SceneError> {
+        let start = self.position;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+            self.source.get(self.position)
+        {
+            self.position += 1;
+        }
+        let text = std::str::from_utf8(&self.source[start..self.position]).unwrap();
+        match text.parse::<f64>() {
+            Ok(number) => Ok(Value::Number(number)),
+            Err(_) => {
+                self.position = start;
+                self.error(format!("invalid number {:?}", text))
+            }
+        }
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, SceneError> {
+        let digits = self.source.get(self.position..self.position + 4);
+        let code = digits
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok());
+        match code {
+            Some(code) => {
+                self.position += 4;
+                Ok(code)
+            }
+            None => self.error("expected four hex digits after `\\u`"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, SceneError> {
+        self.position += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = match self.source.get(self.position) {
+                Some(byte) => *byte,
+                None => return self.error("unterminated string"),
+            };
+            self.position += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self.source.get(self.position).copied();
+                    self.position += 1;
+                    let c = match escape {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let mut code = self.hex_escape()?;
+                            // characters outside the basic plane are escaped
+                            // as a surrogate pair
+                            if (0xd800..0xdc00).contains(&code)
+                                && self.source[self.position..].starts_with(b"\\u")
+                            {
+                                self.position += 2;
+                                let low = self.hex_escape()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return self.error("invalid unicode escape");
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            match char::from_u32(code) {
+                                Some(c) => c,
+                                None => return self.error("invalid unicode escape"),
+                            }
+                        }
+                        _ => return self.error("invalid escape"),
+                    };
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                }
+                byte if byte < 0x20 => return self.error("control character in string"),
+                byte => bytes.push(byte),
+            }
+        }
+        // the source is a `&str` and escapes produce whole characters
+        Ok(String::from_utf8(bytes).unwrap())
+    }
+}
+
+pub fn parse(source: &str) -> Result<Value, SceneError> {
+    let mut parser = Parser {
+        source: source.as_bytes(),
+        position: 0,
+        nesting: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position != parser.source.len() {
+        return parser.error("unexpected text after the end of the document");
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Region;
+    use crate::point::Point;
+    use crate::vector::Vector;
+
+    fn scene() -> Scene {
+        let mut camera = Camera::new(160, 90, 1.0471975511965976);
+        camera.transform = Matrix::view_transform(
+            Point::new(0.1, 1.5, -5.0),
+            Point::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        );
+        camera.sampling = Sampling::Adaptive {
+            n: 3,
+            threshold: 0.05,
+        };
+        camera.crop = Some(Region::new(8, 4, 32, 16));
+
+        let mut world = World::new();
+        world.objects = vec![
+            Sphere::new(
+                Matrix::translation(-0.5, 1.0, 0.5) * Matrix::rotation_y(0.3),
+                Material {
+                    color: Color::new(0.1, 1.0, 0.5),
+                    diffuse: 0.7,
+                    specular: 0.3,
+                    reflective: 0.25,
+                    ..Material::default()
+                },
+            ),
+            Sphere::default(),
+        ];
+        let mut spot = SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.3, -1.0, 0.1),
+            0.2,
+            0.4,
+            Color::new(1.0, 1.0, 1.0),
+        );
+        spot.attenuation = Attenuation::inverse_square();
+        world.lights = vec![
+            Light::from(PointLight::new(
+                Point::new(-10.0, 10.0, -10.0),
+                Color::new(1.0, 1.0, 1.0),
+            )),
+            Light::from(DirectionalLight::new(
+                Vector::new(1.0, -1.0, 1.0),
+                Color::new(0.2, 0.2, 0.2),
+            )),
+            Light::from(spot),
+            Light::from(AreaLight::new(
+                Point::new(-1.0, 2.0, 4.0),
+                Vector::new(2.0, 0.0, 0.0),
+                3,
+                Vector::new(0.0, 2.0, 0.0),
+                7,
+                Color::new(1.5, 1.5, 1.5),
+            )),
+        ];
+
+        Scene { camera, world }
+    }
+
+    fn bits(matrix: &Matrix<4>) -> Vec<u64> {
+        (0..16).map(|i| matrix[i / 4][i % 4].to_bits()).collect()
+    }
+
+    #[test]
+    fn should_round_trip_scene_exactly() {
+        let scene = scene();
+
+        let json = scene.to_json().unwrap();
+        let loaded = Scene::from_json(&json).unwrap();
+
+        assert_eq!(loaded.to_json().unwrap(), json);
+        assert_eq!(
+            bits(&loaded.camera.transform),
+            bits(&scene.camera.transform)
+        );
+        assert_eq!(loaded.camera.sampling, scene.camera.sampling);
+        assert_eq!(loaded.camera.crop, scene.camera.crop);
+        assert_eq!(loaded.world.lights, scene.world.lights);
+        for (loaded, original) in loaded.world.objects.iter().zip(&scene.world.objects) {
+            assert_eq!(
+                bits(&loaded.transform.matrix()),
+                bits(&original.transform.matrix())
+            );
+            assert_eq!(loaded.material, original.material);
+        }
+        if let (Light::Area(loaded), Light::Area(original)) =
+            (loaded.world.lights[3], scene.world.lights[3])
+        {
+            assert_eq!(loaded.vvec.y.to_bits(), original.vvec.y.to_bits());
+        }
+    }
+
+    #[test]
+    fn should_write_readable_json() {
+        let mut scene = scene();
+        scene.world.lights.truncate(1);
+        scene.world.objects.clear();
+        scene.camera.transform = Matrix::identity();
+
+        let json = scene.to_json().unwrap();
+
+        assert!(json.starts_with(
+            r#"{
+  "camera": {
+    "width": 160,
+    "height": 90,
+    "field-of-view": 1.0471975511965976,
+    "transform": [
+      [1, 0, 0, 0],
+      [0, 1, 0, 0],
+      [0, 0, 1, 0],
+      [0, 0, 0, 1]
+    ],
+"#
+        ));
+        assert!(json.ends_with("  \"objects\": []\n}\n"));
+    }
+
+    #[test]
+    fn should_parse_json_values() {
+        let value = parse(r#" { "a": [1.5, -2e3, true, null], "b\né😀": {} } "#).unwrap();
+
+        assert_eq!(
+            value,
+            Value::Object(vec![
+                entry(
+                    "a",
+                    Value::Array(vec![
+                        Value::Number(1.5),
+                        Value::Number(-2000.0),
+                        Value::Bool(true),
+                        Value::Null
+                    ])
+                ),
+                entry("b\né😀", Value::Object(vec![])),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_escape_strings() {
+        let mut json = String::new();
+
+        write_string(&mut json, "a \"b\"\\\n\u{1}");
+
+        assert_eq!(json, r#""a \"b\"\\\n\u0001""#);
+        assert_eq!(parse(&json).unwrap(), name("a \"b\"\\\n\u{1}"));
+    }
+
+    #[test]
+    fn should_report_line_of_json_errors() {
+        let error = parse("{\n  \"a\": 1,\n  \"b\" 2\n}").unwrap_err();
+
+        assert!(matches!(error, SceneError::Syntax { line: 3, .. }));
+    }
+
+    #[test]
+    fn should_reject_trailing_text() {
+        assert!(parse("[1] 2").is_err());
+        assert!(parse("[1,]").is_err());
+        assert!(parse("{\"a\": 1, \"a\": 2}").is_err());
+    }
+
+    #[test]
+    fn should_limit_nesting() {
+        let nested = |levels: usize| "[".repeat(levels) + &"]".repeat(levels);
+
+        assert!(parse(&nested(MAX_NESTING)).is_ok());
+        assert!(matches!(
+            parse(&nested(MAX_NESTING + 1)).unwrap_err(),
+            SceneError::Syntax { line: 1, .. }
+        ));
+        assert!(parse(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn should_refuse_to_write_non_finite_numbers() {
+        let mut scene = scene();
+        scene.camera.field_of_view = f64::INFINITY;
+
+        assert_eq!(
+            scene.to_json().unwrap_err().to_string(),
+            "invalid scene: can't write inf as a JSON number"
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_scenes() {
+        let mut json = scene().to_json().unwrap();
+        json = json.replacen("\"type\": \"sphere\"", "\"type\": \"cube\"", 1);
+
+        assert!(matches!(
+            Scene::from_json(&json).unwrap_err(),
+            SceneError::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn should_reject_invalid_sampling() {
+        let json = scene().to_json().unwrap();
+        let no_samples = json.replacen("\"n\": 3", "\"n\": 0", 1);
+        let negative = json.replacen("\"threshold\": 0.05", "\"threshold\": -0.05", 1);
+
+        for json in [no_samples, negative] {
+            assert!(matches!(
+                Scene::from_json(&json).unwrap_err(),
+                SceneError::Invalid(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn should_reject_area_lights_without_steps() {
+        let json = scene()
+            .to_json()
+            .unwrap()
+            .replacen("\"vsteps\": 7", "\"vsteps\": 0", 1);
+
+        assert_eq!(
+            Scene::from_json(&json).unwrap_err().to_string(),
+            "invalid scene: vsteps must be a positive whole number"
+        );
+    }
+
+    #[test]
+    fn should_default_to_a_material_that_does_not_reflect() {
+        let json = scene().to_json().unwrap();
+        let json = json.replace(",\n        \"reflective\": 0.25", "");
+        assert!(!json.contains("reflective\": 0.25"));
+
+        let loaded = Scene::from_json(&json).unwrap();
+
+        assert_eq!(loaded.world.objects[0].material.reflective, 0.0);
+    }
+}
@@ -0,0 +1,517 @@
+//! Parser for the subset of YAML used by scene files: block sequences and
+//! mappings nested by indentation, single line flow sequences and mappings
+//! (`[1, 2, 3]`, `{ a: 1 }`), plain and quoted scalars and `#` comments.
+
+use crate::scene::value::Value;
+use crate::scene::SceneError;
+
+#[derive(Debug, Clone)]
+struct Line {
+    number: usize,
+    indent: usize,
+    text: String,
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (index, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' && previous.is_whitespace() => return &line[..index],
+            None => {}
+        }
+        previous = c;
+    }
+    line
+}
+
+fn syntax_error(line: usize, message: impl Into<String>) -> SceneError {
+    SceneError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+/// Splits `key: value` into its parts, `value` is empty for `key:`.
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    if text.starts_with('[') || text.starts_with('{') || text.starts_with('"') {
+        return None;
+    }
+    if let Some(key) = text.strip_suffix(':') {
+        return Some((key.trim(), ""));
+    }
+    text.find(": ")
+        .map(|index| (text[..index].trim(), text[index + 2..].trim()))
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// How deeply sequences and mappings may nest, in blocks and flows alike.
+/// Scenes need a handful of levels, the limit keeps hostile input from
+/// overflowing the stack.
+const MAX_NESTING: usize = 128;
+
+fn nesting_error(line: usize) -> SceneError {
+    syntax_error(line, format!("nested deeper than {} levels", MAX_NESTING))
+}
+
+struct Parser {
+    lines: Vec<Line>,
+    position: usize,
+    /// The number of sequences and mappings the parser is inside of.
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Line> {
+        self.lines.get(self.position)
+    }
+
+    fn node(&mut self, indent: usize) -> Result<Value, SceneError> {
+        match self.peek() {
+            Some(line) if is_sequence_item(&line.text) => self.block(Parser::sequence, indent),
+            Some(line) if split_key(&line.text).is_none() => {
+                let value = parse_flow(&line.text, line.number, self.nesting)?;
+                self.position += 1;
+                Ok(value)
+            }
+            Some(_) => self.block(Parser::mapping, indent),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// Parses a sequence or mapping one level deeper than the current one.
+    fn block(
+        &mut self,
+        parse: fn(&mut Self, usize) -> Result<Value, SceneError>,
+        indent: usize,
+    ) -> Result<Value, SceneError> {
+        if self.nesting == MAX_NESTING {
+            let line = self.peek().map_or(0, |line| line.number);
+            return Err(nesting_error(line));
+        }
+        self.nesting += 1;
+        let value = parse(self, indent);
+        self.nesting -= 1;
+        value
+    }
+
+    /// Parses the value following `key:` or a lone `-`, which lives on the
+    /// next lines if they are indented further than `parent`.
+    fn nested(&mut self, parent: usize) -> Result<Value, SceneError> {
+        match self.peek() {
+            Some(line) if line.indent > parent => {
+                let indent = line.indent;
+                self.node(indent)
+            }
+            // sequences may sit at the same indentation as their key
+            Some(line) if line.indent == parent && is_sequence_item(&line.text) => {
+                self.block(Parser::sequence, parent)
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Value, SceneError> {
+        let mut items = Vec::new();
+
+        while let Some(line) = self.peek() {
+            if line.indent < indent || !is_sequence_item(&line.text) {
+                break;
+            }
+            if line.indent > indent {
+                return Err(syntax_error(line.number, "unexpected indentation"));
+            }
+
+            let number = line.number;
+            let rest = line.text[1..].trim_start();
+            let offset = line.text.len() - rest.len();
+
+            if rest.is_empty() {
+                self.position += 1;
+                items.push(self.nested(indent)?);
+            } else if is_sequence_item(rest) || split_key(rest).is_some() {
+                // `- key: value` opens a mapping whose keys line up with `key`
+                let rest = rest.to_string();
+                self.lines[self.position] = Line {
+                    number,
+                    indent: indent + offset,
+                    text: rest,
+                };
+                items.push(self.node(indent + offset)?);
+            } else {
+                let value = parse_flow(rest, number, self.nesting)?;
+                self.position += 1;
+                items.push(value);
+            }
+        }
+
+        Ok(Value::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Value, SceneError> {
+        let mut entries: Vec<(String, Value)> = Vec::new();
+
+        while let Some(line) = self.peek() {
+            if line.indent < indent || (line.indent == indent && is_sequence_item(&line.text)) {
+                break;
+            }
+            if line.indent > indent {
+                return Err(syntax_error(line.number, "unexpected indentation"));
+            }
+
+            let number = line.number;
+            let (key, value) = split_key(&line.text).ok_or_else(|| {
+                syntax_error(
+                    number,
+                    format!("expected `key: value`, found {:?}", line.text),
+                )
+            })?;
+            let key = unquote(key).to_string();
+            let value = value.to_string();
+            if entries.iter().any(|(existing, _)| *existing == key) {
+                return Err(syntax_error(number, format!("duplicate key {:?}", key)));
+            }
+
+            self.position += 1;
+            let value = if value.is_empty() {
+                self.nested(indent)?
+            } else {
+                parse_flow(&value, number, self.nesting)?
+            };
+            entries.push((key, value));
+        }
+
+        Ok(Value::Object(entries))
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    for quote in ['"', '\''] {
+        if text.len() >= 2 && text.starts_with(quote) && text.ends_with(quote) {
+            return &text[1..text.len() - 1];
+        }
+    }
+    text
+}
+
+fn scalar(text: &str) -> Value {
+    match text {
+        "" | "~" | "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ if text.starts_with('"') || text.starts_with('\'') => {
+            Value::String(unquote(text).to_string())
+        }
+        _ => match text.parse::<f64>() {
+            Ok(number) => Value::Number(number),
+            Err(_) => Value::String(text.to_string()),
+        },
+    }
+}
+
+/// Parses a value written on a single line, like `0.5`, `white` or
+/// `[ translate, 1, 2, 3 ]`, inside `nesting` block sequences and mappings.
+fn parse_flow(text: &str, line: usize, nesting: usize) -> Result<Value, SceneError> {
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut position = 0;
+    let value = flow_value(&chars, &mut position, line, nesting)?;
+    skip_spaces(&chars, &mut position);
+    if position != chars.len() {
+        return Err(syntax_error(
+            line,
+            format!(
+                "unexpected {:?} after value",
+                chars[position..].iter().collect::<String>()
+            ),
+        ));
+    }
+    Ok(value)
+}
+
+fn skip_spaces(chars: &[char], position: &mut usize) {
+    while *position < chars.len() && chars[*position].is_whitespace() {
+        *position += 1;
+    }
+}
+
+fn flow_value(
+    chars: &[char],
+    position: &mut usize,
+    line: usize,
+    nesting: usize,
+) -> Result<Value, SceneError> {
+    skip_spaces(chars, position);
+    match chars.get(*position) {
+        Some('[' | '{') if nesting == MAX_NESTING => Err(nesting_error(line)),
+        Some('[') => {
+            *position += 1;
+            let mut items = Vec::new();
+            loop {
+                skip_spaces(chars, position);
+                if chars.get(*position) == Some(&']') {
+                    *position += 1;
+                    return Ok(Value::Array(items));
+                }
+                items.push(flow_value(chars, position, line, nesting + 1)?);
+                skip_spaces(chars, position);
+                match chars.get(*position) {
+                    Some(',') => *position += 1,
+                    Some(']') => {}
+                    _ => return Err(syntax_error(line, "expected `,` or `]` in sequence")),
+                }
+            }
+        }
+        Some('{') => {
+            *position += 1;
+            let mut entries = Vec::new();
+            loop {
+                skip_spaces(chars, position);
+                if chars.get(*position) == Some(&'}') {
+                    *position += 1;
+                    return Ok(Value::Object(entries));
+                }
+                let key = flow_token(chars, position, true);
+                skip_spaces(chars, position);
+                if chars.get(*position) != Some(&':') {
+                    return Err(syntax_error(line, "expected `:` in mapping"));
+                }
+                *position += 1;
+                let value = flow_value(chars, position, line, nesting + 1)?;
+                entries.push((unquote(&key).to_string(), value));
+                skip_spaces(chars, position);
+                match chars.get(*position) {
+                    Some(',') => *position += 1,
+                    Some('}') => {}
+                    _ => return Err(syntax_error(line, "expected `,` or `}` in mapping")),
+                }
+            }
+        }
+        Some(_) => Ok(scalar(&flow_token(chars, position, false))),
+        None => Err(syntax_error(line, "expected a value")),
+    }
+}
+
+fn flow_token(chars: &[char], position: &mut usize, is_key: bool) -> String {
+    let start = *position;
+    if let Some(&quote) = chars.get(start).filter(|c| **c == '"' || **c == '\'') {
+        *position += 1;
+        while *position < chars.len() && chars[*position] != quote {
+            *position += 1;
+        }
+        *position = (*position + 1).min(chars.len());
+    } else {
+        while *position < chars.len() {
+            let c = chars[*position];
+            if c == ',' || c == ']' || c == '}' || (is_key && c == ':') {
+                break;
+            }
+            *position += 1;
+        }
+    }
+    chars[start..*position]
+        .iter()
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+pub fn parse(source: &str) -> Result<Value, SceneError> {
+    let mut lines = Vec::new();
+    for (index, raw) in source.lines().enumerate() {
+        let text = strip_comment(raw).trim_end();
+        if text.trim().is_empty() || text == "---" {
+            continue;
+        }
+        if text.starts_with('\t') {
+            return Err(syntax_error(
+                index + 1,
+                "tabs are not allowed for indentation",
+            ));
+        }
+        let trimmed = text.trim_start();
+        lines.push(Line {
+            number: index + 1,
+            indent: text.len() - trimmed.len(),
+            text: trimmed.to_string(),
+        });
+    }
+
+    let mut parser = Parser {
+        lines,
+        position: 0,
+        nesting: 0,
+    };
+    let indent = parser.peek().map_or(0, |line| line.indent);
+    let value = parser.node(indent)?;
+
+    match parser.peek() {
+        Some(line) => Err(syntax_error(line.number, "unexpected indentation")),
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn should_parse_scalars() {
+        assert_eq!(parse("1.5").unwrap(), Value::Number(1.5));
+        assert_eq!(parse("-3").unwrap(), Value::Number(-3.0));
+        assert_eq!(parse("true").unwrap(), Value::Bool(true));
+        assert_eq!(parse("white-material").unwrap(), string("white-material"));
+        assert_eq!(
+            parse("\"quoted # not a comment\"").unwrap(),
+            string("quoted # not a comment")
+        );
+    }
+
+    #[test]
+    fn should_parse_flow_sequences() {
+        let expected = Value::Array(vec![
+            string("translate"),
+            Value::Number(1.0),
+            Value::Number(-0.5),
+            Value::Array(vec![]),
+        ]);
+
+        assert_eq!(parse("[ translate, 1, -0.5, [] ]").unwrap(), expected);
+    }
+
+    #[test]
+    fn should_parse_flow_mappings() {
+        let expected = Value::Object(vec![
+            ("color".to_string(), Value::Array(vec![Value::Number(1.0)])),
+            ("diffuse".to_string(), Value::Number(0.7)),
+        ]);
+
+        assert_eq!(parse("{ color: [1], diffuse: 0.7 }").unwrap(), expected);
+    }
+
+    #[test]
+    fn should_parse_sequence_of_mappings() {
+        let yaml = r"
+# a comment
+- add: camera
+  width: 100
+  from: [ 0, 1.5, -5 ] # trailing comment
+
+- add: light
+  at: [ -10, 10, -10 ]
+";
+
+        let expected = Value::Array(vec![
+            Value::Object(vec![
+                ("add".to_string(), string("camera")),
+                ("width".to_string(), Value::Number(100.0)),
+                (
+                    "from".to_string(),
+                    Value::Array(vec![
+                        Value::Number(0.0),
+                        Value::Number(1.5),
+                        Value::Number(-5.0),
+                    ]),
+                ),
+            ]),
+            Value::Object(vec![
+                ("add".to_string(), string("light")),
+                (
+                    "at".to_string(),
+                    Value::Array(vec![
+                        Value::Number(-10.0),
+                        Value::Number(10.0),
+                        Value::Number(-10.0),
+                    ]),
+                ),
+            ]),
+        ]);
+
+        assert_eq!(parse(yaml).unwrap(), expected);
+    }
+
+    #[test]
+    fn should_parse_nested_blocks() {
+        let yaml = r"
+- define: standard-transform
+  value:
+    - [ translate, 1, -1, 1 ]
+    - [ scale, 0.5, 0.5, 0.5 ]
+- add: sphere
+  material:
+    color: [ 1, 0, 0 ]
+  transform:
+  - standard-transform
+";
+
+        let value = parse(yaml).unwrap();
+        let items = value.as_array().unwrap();
+
+        let transform = items[0].get("value").unwrap().as_array().unwrap();
+        assert_eq!(transform.len(), 2);
+        assert_eq!(transform[1].as_array().unwrap()[0], string("scale"));
+
+        let material = items[1].get("material").unwrap();
+        assert!(material.get("color").is_some());
+        assert_eq!(
+            items[1].get("transform").unwrap(),
+            &Value::Array(vec![string("standard-transform")])
+        );
+    }
+
+    #[test]
+    fn should_report_line_of_syntax_errors() {
+        let yaml = "- add: sphere\n  material: [ 1, 2\n";
+
+        let error = parse(yaml).unwrap_err();
+
+        assert!(matches!(error, SceneError::Syntax { line: 2, .. }));
+    }
+
+    #[test]
+    fn should_reject_inconsistent_indentation() {
+        let yaml = "- add: sphere\n  material: white\n     shininess: 5\n";
+
+        let error = parse(yaml).unwrap_err();
+
+        assert!(matches!(error, SceneError::Syntax { line: 3, .. }));
+    }
+
+    #[test]
+    fn should_reject_duplicate_keys() {
+        let yaml = "width: 1\nwidth: 2\n";
+
+        let error = parse(yaml).unwrap_err();
+
+        assert!(matches!(error, SceneError::Syntax { line: 2, .. }));
+    }
+
+    #[test]
+    fn should_limit_nesting() {
+        let flow = |levels: usize| "[".repeat(levels) + &"]".repeat(levels);
+        let block = |levels: usize| "- ".repeat(levels) + "1";
+
+        assert!(parse(&flow(MAX_NESTING)).is_ok());
+        assert!(parse(&block(MAX_NESTING)).is_ok());
+        assert!(matches!(
+            parse(&flow(MAX_NESTING + 1)).unwrap_err(),
+            SceneError::Syntax { line: 1, .. }
+        ));
+        assert!(matches!(
+            parse(&block(MAX_NESTING + 1)).unwrap_err(),
+            SceneError::Syntax { line: 1, .. }
+        ));
+        assert!(parse(&format!("- {}", flow(MAX_NESTING))).is_err());
+        assert!(parse(&flow(100_000)).is_err());
+        assert!(parse(&block(100_000)).is_err());
+    }
+}
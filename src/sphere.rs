@@ -0,0 +1,141 @@
+use crate::intersection::Intersection;
+use crate::material::Material;
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::transform::Transform;
+use crate::vector::Vector;
+
+/// A unit sphere around the origin, moved into place by `transform`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sphere {
+    pub transform: Transform,
+    pub material: Material,
+}
+
+impl Sphere {
+    pub fn new(transform: impl Into<Transform>, material: Material) -> Self {
+        Self {
+            transform: transform.into(),
+            material,
+        }
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let ray = self.transform.ray_to_object(ray);
+        let sphere_to_ray = ray.origin - Point::new(0.0, 0.0, 0.0);
+
+        let a = ray.direction.dot(&ray.direction);
+        let b = 2.0 * ray.direction.dot(&sphere_to_ray);
+        let c = sphere_to_ray.dot(&sphere_to_ray) - 1.0;
+        let discriminant = b * b - 4.0 * a * c;
+
+        if discriminant < 0.0 {
+            return vec![];
+        }
+
+        let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+        let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+        vec![Intersection::new(t1, self), Intersection::new(t2, self)]
+    }
+
+    pub fn normal_at(&self, world_point: Point) -> Vector {
+        let object_point = self.transform.point_to_object(world_point);
+        let object_normal = object_point - Point::new(0.0, 0.0, 0.0);
+        self.transform.normal_to_world(object_normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Matrix;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_intersect_a_sphere_at_two_points() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::default();
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert_eq!(xs[1].t, 6.0);
+    }
+
+    #[test]
+    fn should_intersect_a_sphere_at_a_tangent() {
+        let ray = Ray::new(Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::default();
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 5.0);
+        assert_eq!(xs[1].t, 5.0);
+    }
+
+    #[test]
+    fn should_miss_a_sphere() {
+        let ray = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::default();
+
+        assert!(sphere.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_intersect_a_sphere_from_inside() {
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::default();
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs[0].t, -1.0);
+        assert_eq!(xs[1].t, 1.0);
+    }
+
+    #[test]
+    fn should_intersect_a_scaled_sphere() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new(Matrix::scaling(2.0, 2.0, 2.0), Material::default());
+
+        let xs = sphere.intersect(&ray);
+
+        assert_eq!(xs[0].t, 3.0);
+        assert_eq!(xs[1].t, 7.0);
+    }
+
+    #[test]
+    fn should_miss_a_translated_sphere() {
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let sphere = Sphere::new(Matrix::translation(5.0, 0.0, 0.0), Material::default());
+
+        assert!(sphere.intersect(&ray).is_empty());
+    }
+
+    #[test]
+    fn should_compute_normal_on_a_sphere() {
+        let sphere = Sphere::default();
+        let third_sqrt = 3.0_f64.sqrt() / 3.0;
+
+        assert_eq!(
+            sphere.normal_at(Point::new(1.0, 0.0, 0.0)),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            sphere.normal_at(Point::new(third_sqrt, third_sqrt, third_sqrt)),
+            Vector::new(third_sqrt, third_sqrt, third_sqrt)
+        );
+    }
+
+    #[test]
+    fn should_compute_normal_on_a_transformed_sphere() {
+        let transform = Matrix::scaling(1.0, 0.5, 1.0) * Matrix::rotation_z(PI / 5.0);
+        let sphere = Sphere::new(transform, Material::default());
+        let half_sqrt = 2.0_f64.sqrt() / 2.0;
+
+        let actual = sphere.normal_at(Point::new(0.0, half_sqrt, -half_sqrt));
+
+        assert_eq!(actual, Vector::new(0.0, 0.97014, -0.24254));
+    }
+}
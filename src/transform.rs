@@ -0,0 +1,224 @@
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::vector::Vector;
+
+/// A transformation matrix together with its inverse and inverse transpose,
+/// which intersecting and shading need for every ray.
+///
+/// Operations chain in the order they are applied, so
+/// `Transform::identity().rotate_x(r).scale(2.0, 2.0, 2.0)` rotates first
+/// and scales afterwards. The inverse is updated from the inverse of each
+/// operation instead of inverting the product.
+#[derive(Debug, Copy, Clone)]
+pub struct Transform {
+    matrix: Matrix<4>,
+    inverse: Matrix<4>,
+    inverse_transpose: Matrix<4>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Matrix<4>> for Transform {
+    /// Panics if the matrix can't be inverted, see [`Transform::try_new`].
+    fn from(matrix: Matrix<4>) -> Self {
+        Self::new(matrix)
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self::with_inverse(Matrix::identity(), Matrix::identity())
+    }
+
+    pub fn new(matrix: Matrix<4>) -> Self {
+        Self::with_inverse(matrix, matrix.inverse())
+    }
+
+    /// Returns `None` for matrices that can't be inverted, like a scaling
+    /// by zero.
+    pub fn try_new(matrix: Matrix<4>) -> Option<Self> {
+        Some(Self::with_inverse(matrix, matrix.try_inverse()?))
+    }
+
+    fn with_inverse(matrix: Matrix<4>, inverse: Matrix<4>) -> Self {
+        Self {
+            matrix,
+            inverse,
+            inverse_transpose: inverse.transpose(),
+        }
+    }
+
+    pub fn matrix(&self) -> Matrix<4> {
+        self.matrix
+    }
+
+    pub fn inverse(&self) -> Matrix<4> {
+        self.inverse
+    }
+
+    pub fn inverse_transpose(&self) -> Matrix<4> {
+        self.inverse_transpose
+    }
+
+    /// Applies `other` after this transform.
+    pub fn then(&self, other: &Transform) -> Self {
+        Self::with_inverse(other.matrix * self.matrix, self.inverse * other.inverse)
+    }
+
+    pub fn translate(&self, x: f64, y: f64, z: f64) -> Self {
+        self.then_with_inverse(
+            Matrix::translation(x, y, z),
+            Matrix::translation(-x, -y, -z),
+        )
+    }
+
+    /// Panics if any factor is zero.
+    pub fn scale(&self, x: f64, y: f64, z: f64) -> Self {
+        assert!(
+            x != 0.0 && y != 0.0 && z != 0.0,
+            "tried to scale by zero, which can't be inverted"
+        );
+        self.then_with_inverse(
+            Matrix::scaling(x, y, z),
+            Matrix::scaling(1.0 / x, 1.0 / y, 1.0 / z),
+        )
+    }
+
+    pub fn rotate_x(&self, r: f64) -> Self {
+        self.then_with_inverse(Matrix::rotation_x(r), Matrix::rotation_x(r).transpose())
+    }
+
+    pub fn rotate_y(&self, r: f64) -> Self {
+        self.then_with_inverse(Matrix::rotation_y(r), Matrix::rotation_y(r).transpose())
+    }
+
+    pub fn rotate_z(&self, r: f64) -> Self {
+        self.then_with_inverse(Matrix::rotation_z(r), Matrix::rotation_z(r).transpose())
+    }
+
+    pub fn rotate_axis(&self, axis: Vector, r: f64) -> Self {
+        let rotation = Matrix::rotation_axis(axis, r);
+        self.then_with_inverse(rotation, rotation.transpose())
+    }
+
+    /// Panics if the shear can't be inverted.
+    pub fn shear(&self, x_y: f64, x_z: f64, y_x: f64, y_z: f64, z_x: f64, z_y: f64) -> Self {
+        self.then(&Transform::new(Matrix::shearing(
+            x_y, x_z, y_x, y_z, z_x, z_y,
+        )))
+    }
+
+    fn then_with_inverse(&self, matrix: Matrix<4>, inverse: Matrix<4>) -> Self {
+        self.then(&Self::with_inverse(matrix, inverse))
+    }
+
+    /// Moves a world space ray into the space this transform maps from.
+    pub fn ray_to_object(&self, ray: &Ray) -> Ray {
+        ray.transform(self.inverse)
+    }
+
+    pub fn point_to_object(&self, point: Point) -> Point {
+        self.inverse * point
+    }
+
+    /// Normals don't stay perpendicular under the transform itself, they
+    /// need the inverse transpose.
+    pub fn normal_to_world(&self, normal: Vector) -> Vector {
+        (self.inverse_transpose * normal).normalize()
+    }
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.matrix == other.matrix
+    }
+}
+
+impl PartialEq<Matrix<4>> for Transform {
+    fn eq(&self, other: &Matrix<4>) -> bool {
+        self.matrix == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn should_apply_operations_in_chained_order() {
+        let transform = Transform::identity()
+            .rotate_x(PI / 2.0)
+            .scale(5.0, 5.0, 5.0)
+            .translate(10.0, 5.0, 7.0);
+
+        let expected = Matrix::translation(10.0, 5.0, 7.0)
+            * Matrix::scaling(5.0, 5.0, 5.0)
+            * Matrix::rotation_x(PI / 2.0);
+
+        assert_eq!(transform, expected);
+        assert_eq!(
+            transform.matrix() * Point::new(1.0, 0.0, 1.0),
+            Point::new(15.0, 0.0, 7.0)
+        );
+    }
+
+    #[test]
+    fn should_keep_inverse_in_step_with_matrix() {
+        let transform = Transform::identity()
+            .rotate_y(PI / 3.0)
+            .shear(1.0, 0.0, 0.5, 0.0, 0.0, 2.0)
+            .scale(2.0, 0.5, 3.0)
+            .rotate_z(PI / 7.0)
+            .translate(-1.0, 4.0, 2.5);
+
+        assert_eq!(transform.inverse(), transform.matrix().inverse());
+        assert_eq!(
+            transform.inverse_transpose(),
+            transform.matrix().inverse().transpose()
+        );
+        assert_eq!(transform.matrix() * transform.inverse(), Matrix::identity());
+    }
+
+    #[test]
+    fn should_chain_transforms_with_then() {
+        let a = Transform::identity().rotate_x(PI / 4.0);
+        let b = Transform::new(Matrix::translation(1.0, 2.0, 3.0));
+
+        let combined = a.then(&b);
+
+        assert_eq!(
+            combined,
+            Matrix::translation(1.0, 2.0, 3.0) * Matrix::rotation_x(PI / 4.0)
+        );
+        assert_eq!(combined.inverse(), combined.matrix().inverse());
+    }
+
+    #[test]
+    fn should_reject_singular_matrices() {
+        assert!(Transform::try_new(Matrix::scaling(1.0, 0.0, 1.0)).is_none());
+        assert!(Transform::try_new(Matrix::scaling(1.0, 2.0, 1.0)).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "scale by zero")]
+    fn should_panic_when_scaling_by_zero() {
+        Transform::identity().scale(0.0, 1.0, 1.0);
+    }
+
+    #[test]
+    fn should_transform_normals_with_inverse_transpose() {
+        let transform = Transform::identity().scale(1.0, 0.5, 1.0);
+        let half_sqrt = 2.0_f64.sqrt() / 2.0;
+
+        assert_eq!(
+            transform.normal_to_world(Vector::new(0.0, half_sqrt, -half_sqrt)),
+            Vector::new(0.0, 0.89443, -0.44721)
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::equal;
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::vector::Vector;
+use std::ops;
+
+/// Homogeneous coordinates with an explicit `w`. Points have a `w` of 1 and
+/// vectors a `w` of 0, projections produce anything else.
+#[derive(Debug, Copy, Clone)]
+pub struct Tuple4 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+impl Tuple4 {
+    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn is_point(&self) -> bool {
+        equal(self.w, 1.0)
+    }
+
+    pub fn is_vector(&self) -> bool {
+        equal(self.w, 0.0)
+    }
+
+    pub fn dot(&self, other: &Tuple4) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// Divides by `w` to get back to 3D. Returns `None` when `w` is 0, for
+    /// vectors and points on the plane through the eye of a projection.
+    pub fn perspective_divide(&self) -> Option<Point> {
+        if self.w == 0.0 {
+            return None;
+        }
+        Some(Point::new(
+            self.x / self.w,
+            self.y / self.w,
+            self.z / self.w,
+        ))
+    }
+
+    /// Drops `w`, which is right for vectors but not for other tuples.
+    pub fn xyz(&self) -> Vector {
+        Vector::new(self.x, self.y, self.z)
+    }
+}
+
+impl From<[f64; 4]> for Tuple4 {
+    fn from(array: [f64; 4]) -> Self {
+        Self::new(array[0], array[1], array[2], array[3])
+    }
+}
+
+impl From<Point> for Tuple4 {
+    fn from(point: Point) -> Self {
+        Self::new(point.x, point.y, point.z, 1.0)
+    }
+}
+
+impl From<Vector> for Tuple4 {
+    fn from(vector: Vector) -> Self {
+        Self::new(vector.x, vector.y, vector.z, 0.0)
+    }
+}
+
+impl PartialEq for Tuple4 {
+    fn eq(&self, other: &Self) -> bool {
+        equal(self.x, other.x)
+            && equal(self.y, other.y)
+            && equal(self.z, other.z)
+            && equal(self.w, other.w)
+    }
+}
+
+impl ops::Add for Tuple4 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.x + other.x,
+            self.y + other.y,
+            self.z + other.z,
+            self.w + other.w,
+        )
+    }
+}
+
+impl ops::Sub for Tuple4 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(
+            self.x - other.x,
+            self.y - other.y,
+            self.z - other.z,
+            self.w - other.w,
+        )
+    }
+}
+
+impl ops::Neg for Tuple4 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl ops::Mul<f64> for Tuple4 {
+    type Output = Self;
+
+    fn mul(self, scalar: f64) -> Self {
+        Self::new(
+            self.x * scalar,
+            self.y * scalar,
+            self.z * scalar,
+            self.w * scalar,
+        )
+    }
+}
+
+impl ops::Div<f64> for Tuple4 {
+    type Output = Self;
+
+    fn div(self, scalar: f64) -> Self {
+        Self::new(
+            self.x / scalar,
+            self.y / scalar,
+            self.z / scalar,
+            self.w / scalar,
+        )
+    }
+}
+
+/// Uses the bottom row as well, unlike multiplying a `Point` or `Vector`.
+impl ops::Mul<Tuple4> for Matrix<4> {
+    type Output = Tuple4;
+
+    fn mul(self, other: Tuple4) -> Self::Output {
+        let mut result = [0.0; 4];
+        for row in 0..4 {
+            result[row] = self[row][0] * other.x
+                + self[row][1] * other.y
+                + self[row][2] * other.z
+                + self[row][3] * other.w
+        }
+
+        Tuple4::from(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_points_and_vectors() {
+        let point = Tuple4::from(Point::new(4.0, -4.0, 3.0));
+        let vector = Tuple4::from(Vector::new(4.0, -4.0, 3.0));
+
+        assert_eq!(point, Tuple4::new(4.0, -4.0, 3.0, 1.0));
+        assert!(point.is_point() && !point.is_vector());
+        assert_eq!(vector, Tuple4::new(4.0, -4.0, 3.0, 0.0));
+        assert!(vector.is_vector() && !vector.is_point());
+        assert_eq!(vector.xyz(), Vector::new(4.0, -4.0, 3.0));
+    }
+
+    #[test]
+    fn should_divide_by_w() {
+        let tuple = Tuple4::new(2.0, -4.0, 6.0, 2.0);
+
+        assert_eq!(tuple.perspective_divide(), Some(Point::new(1.0, -2.0, 3.0)));
+        assert_eq!(Tuple4::new(1.0, 2.0, 3.0, 0.0).perspective_divide(), None);
+    }
+
+    #[test]
+    fn should_agree_with_point_and_vector_multiplication() {
+        let matrix = Matrix::translation(1.0, 2.0, 3.0) * Matrix::scaling(2.0, 2.0, 2.0);
+        let point = Point::new(1.0, -1.0, 0.5);
+        let vector = Vector::new(1.0, -1.0, 0.5);
+
+        assert_eq!(
+            (matrix * Tuple4::from(point)).perspective_divide(),
+            Some(matrix * point)
+        );
+        assert_eq!(matrix * Tuple4::from(vector), Tuple4::from(matrix * vector));
+    }
+
+    #[test]
+    fn should_keep_w_from_the_bottom_row() {
+        let mut projection = Matrix::identity();
+        projection[3] = [0.0, 0.0, 1.0, 0.0];
+
+        let projected = projection * Tuple4::from(Point::new(2.0, 4.0, 2.0));
+
+        assert_eq!(projected, Tuple4::new(2.0, 4.0, 2.0, 2.0));
+        assert_eq!(
+            projected.perspective_divide(),
+            Some(Point::new(1.0, 2.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn should_do_tuple_arithmetic() {
+        let a = Tuple4::new(3.0, -2.0, 5.0, 1.0);
+        let b = Tuple4::new(-2.0, 3.0, 1.0, 0.0);
+
+        assert_eq!(a + b, Tuple4::new(1.0, 1.0, 6.0, 1.0));
+        assert_eq!(a - b, Tuple4::new(5.0, -5.0, 4.0, 1.0));
+        assert_eq!(-a * 2.0, Tuple4::new(-6.0, 4.0, -10.0, -2.0));
+        assert_eq!(a / 2.0, Tuple4::new(1.5, -1.0, 2.5, 0.5));
+        assert_eq!(a.dot(&b), -7.0);
+    }
+}
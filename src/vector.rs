@@ -1,21 +1,42 @@
+use crate::approx::ApproxEq;
+#[cfg(test)]
 use crate::equal;
+use crate::float::Float;
+use std::iter;
 use std::ops;
 
+/// Names a component of a `Vector` or `Point`, for indexing with `v[Axis::Y]`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+}
+
 #[derive(Debug, Copy, Clone)]
-pub struct Vector {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+pub struct Vector<T = f64> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl Vector {
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+impl<T: Float> Vector<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
         Self { x, y, z }
     }
 
-    pub fn magnitude(&self) -> f64 {
-        let sum_of_squares = self.x.powf(2.0) + self.y.powf(2.0) + self.z.powf(2.0);
-        sum_of_squares.sqrt()
+    /// Converts to another precision.
+    pub fn cast<U: Float>(&self) -> Vector<U> {
+        let convert = |value: T| U::from_f64(value.to_f64());
+        Vector::new(convert(self.x), convert(self.y), convert(self.z))
+    }
+
+    pub fn magnitude(&self) -> T {
+        self.dot(self).sqrt()
     }
 
     pub fn normalize(&self) -> Self {
@@ -27,7 +48,7 @@ impl Vector {
         }
     }
 
-    pub fn dot(&self, other: &Vector) -> f64 {
+    pub fn dot(&self, other: &Self) -> T {
         self.x * other.x + self.y * other.y + self.z * other.z
     }
 
@@ -38,16 +59,109 @@ impl Vector {
             z: self.x * other.y - self.y * other.x,
         }
     }
+
+    pub fn reflect(&self, normal: &Self) -> Self {
+        *self - *normal * T::from_f64(2.0) * self.dot(normal)
+    }
+
+    /// Goes from this vector at `t` = 0 to `other` at `t` = 1.
+    pub fn lerp(&self, other: &Self, t: T) -> Self {
+        *self + (*other - *self) * t
+    }
+
+    pub fn min(&self, other: &Self) -> Self {
+        Self::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    pub fn max(&self, other: &Self) -> Self {
+        Self::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    /// In radians, between 0 and π. Stays accurate for nearly parallel
+    /// vectors, where `acos` of the dot product doesn't.
+    pub fn angle_between(&self, other: &Self) -> T {
+        self.cross(other).magnitude().atan2(self.dot(other))
+    }
+
+    /// The part of this vector pointing along `other`.
+    pub fn project_onto(&self, other: &Self) -> Self {
+        *other * (self.dot(other) / other.dot(other))
+    }
+
+    /// The part of this vector perpendicular to `other`, what remains after
+    /// taking away the projection.
+    pub fn reject_from(&self, other: &Self) -> Self {
+        *self - self.project_onto(other)
+    }
+
+    /// Two unit vectors that together with this normalized vector make a
+    /// right handed orthonormal basis, so `tangent × bitangent` is `self`.
+    /// Without branching on which axis is closest, see Duff et al.,
+    /// "Building an Orthonormal Basis, Revisited".
+    pub fn orthonormal_basis(&self) -> (Self, Self) {
+        let sign = T::ONE.copysign(self.z);
+        let a = -T::ONE / (sign + self.z);
+        let b = self.x * self.y * a;
+        let tangent = Self::new(
+            T::ONE + sign * self.x * self.x * a,
+            sign * b,
+            -sign * self.x,
+        );
+        let bitangent = Self::new(b, sign + self.y * self.y * a, -self.y);
+        (tangent, bitangent)
+    }
+}
+
+impl<T> ops::Index<Axis> for Vector<T> {
+    type Output = T;
+
+    fn index(&self, axis: Axis) -> &T {
+        match axis {
+            Axis::X => &self.x,
+            Axis::Y => &self.y,
+            Axis::Z => &self.z,
+        }
+    }
 }
 
-impl PartialEq for Vector {
+impl<T> ops::IndexMut<Axis> for Vector<T> {
+    fn index_mut(&mut self, axis: Axis) -> &mut T {
+        match axis {
+            Axis::X => &mut self.x,
+            Axis::Y => &mut self.y,
+            Axis::Z => &mut self.z,
+        }
+    }
+}
+
+impl<T: Float> PartialEq for Vector<T> {
     fn eq(&self, other: &Self) -> bool {
-        equal(self.x, other.x) && equal(self.y, other.y) && equal(self.z, other.z)
+        self.x.is_close(other.x) && self.y.is_close(other.y) && self.z.is_close(other.z)
     }
 }
 
-impl From<[f64; 3]> for Vector {
-    fn from(array: [f64; 3]) -> Self {
+impl<T: Float> ApproxEq for Vector<T> {
+    type Scalar = T;
+
+    fn all_components(&self, other: &Self, eq: impl Fn(T, T) -> bool) -> bool {
+        eq(self.x, other.x) && eq(self.y, other.y) && eq(self.z, other.z)
+    }
+}
+
+impl<T: Copy> From<[T; 3]> for Vector<T> {
+    fn from(array: [T; 3]) -> Self {
         Self {
             x: array[0],
             y: array[1],
@@ -56,7 +170,7 @@ impl From<[f64; 3]> for Vector {
     }
 }
 
-impl ops::Add<Vector> for Vector {
+impl<T: Float> ops::Add for Vector<T> {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
@@ -68,7 +182,21 @@ impl ops::Add<Vector> for Vector {
     }
 }
 
-impl ops::Sub for Vector {
+impl<T: Float> iter::Sum for Vector<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(T::ZERO, T::ZERO, T::ZERO), |sum, vector| {
+            sum + vector
+        })
+    }
+}
+
+impl<'a, T: Float> iter::Sum<&'a Vector<T>> for Vector<T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl<T: Float> ops::Sub for Vector<T> {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
@@ -80,7 +208,7 @@ impl ops::Sub for Vector {
     }
 }
 
-impl ops::Neg for Vector {
+impl<T: Float> ops::Neg for Vector<T> {
     type Output = Self;
 
     fn neg(self) -> Self {
@@ -92,10 +220,10 @@ impl ops::Neg for Vector {
     }
 }
 
-impl ops::Mul<f64> for Vector {
+impl<T: Float> ops::Mul<T> for Vector<T> {
     type Output = Self;
 
-    fn mul(self, scalar: f64) -> Self {
+    fn mul(self, scalar: T) -> Self {
         Self {
             x: self.x * scalar,
             y: self.y * scalar,
@@ -104,10 +232,10 @@ impl ops::Mul<f64> for Vector {
     }
 }
 
-impl ops::Div<f64> for Vector {
+impl<T: Float> ops::Div<T> for Vector<T> {
     type Output = Self;
 
-    fn div(self, scalar: f64) -> Self {
+    fn div(self, scalar: T) -> Self {
         Self {
             x: self.x / scalar,
             y: self.y / scalar,
@@ -264,3 +392,138 @@ fn should_calculate_cross_product_of_two_vectors() {
 
     assert_eq!(expected, actual);
 }
+
+#[test]
+fn should_reflect_a_vector_approaching_at_45_degrees() {
+    let v = Vector::new(1.0, -1.0, 0.0);
+    let n = Vector::new(0.0, 1.0, 0.0);
+
+    let expected = Vector::new(1.0, 1.0, 0.0);
+    let actual = v.reflect(&n);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_reflect_a_vector_off_a_slanted_surface() {
+    let v = Vector::new(0.0, -1.0, 0.0);
+    let n = Vector::new(2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0, 0.0);
+
+    let expected = Vector::new(1.0, 0.0, 0.0);
+    let actual = v.reflect(&n);
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_interpolate_between_vectors() {
+    let a = Vector::new(1.0, 2.0, 3.0);
+    let b = Vector::new(3.0, -2.0, 3.0);
+
+    assert_eq!(a.lerp(&b, 0.0), a);
+    assert_eq!(a.lerp(&b, 0.25), Vector::new(1.5, 1.0, 3.0));
+    assert_eq!(a.lerp(&b, 1.0), b);
+}
+
+#[test]
+fn should_take_component_wise_min_max_and_abs() {
+    let a = Vector::new(1.0, -5.0, 3.0);
+    let b = Vector::new(-2.0, 4.0, 3.0);
+
+    assert_eq!(a.min(&b), Vector::new(-2.0, -5.0, 3.0));
+    assert_eq!(a.max(&b), Vector::new(1.0, 4.0, 3.0));
+    assert_eq!(a.abs(), Vector::new(1.0, 5.0, 3.0));
+}
+
+#[test]
+fn should_calculate_angle_between_vectors() {
+    use std::f64::consts::PI;
+    let x = Vector::new(2.0, 0.0, 0.0);
+
+    assert!(equal(
+        x.angle_between(&Vector::new(0.0, 0.0, 3.0)),
+        PI / 2.0
+    ));
+    assert!(equal(
+        x.angle_between(&Vector::new(1.0, 1.0, 0.0)),
+        PI / 4.0
+    ));
+    assert!(equal(x.angle_between(&-x), PI));
+    assert!(equal(x.angle_between(&Vector::new(1.0, 1e-9, 0.0)), 1e-9));
+}
+
+#[test]
+fn should_project_and_reject_vectors() {
+    let v = Vector::new(3.0, 4.0, 5.0);
+    let onto = Vector::new(0.0, 2.0, 0.0);
+
+    assert_eq!(v.project_onto(&onto), Vector::new(0.0, 4.0, 0.0));
+    assert_eq!(v.reject_from(&onto), Vector::new(3.0, 0.0, 5.0));
+}
+
+#[test]
+fn should_build_orthonormal_basis_around_normal() {
+    let normals = [
+        Vector::new(0.0, 0.0, 1.0),
+        Vector::new(0.0, 0.0, -1.0),
+        Vector::new(1.0, 0.0, 0.0),
+        Vector::new(1.0, -2.0, 0.5).normalize(),
+        Vector::new(-0.3, 0.1, -2.0).normalize(),
+    ];
+
+    for normal in normals {
+        let (tangent, bitangent) = normal.orthonormal_basis();
+
+        assert!(equal(tangent.magnitude(), 1.0));
+        assert!(equal(bitangent.magnitude(), 1.0));
+        assert!(equal(tangent.dot(&normal), 0.0));
+        assert!(equal(bitangent.dot(&normal), 0.0));
+        assert_eq!(tangent.cross(&bitangent), normal);
+    }
+}
+
+#[test]
+fn should_index_vector_by_axis() {
+    let mut v = Vector::new(1.0, 2.0, 3.0);
+    v[Axis::Y] = 5.0;
+
+    assert_eq!(v[Axis::X], 1.0);
+    assert_eq!(v[Axis::Y], 5.0);
+    assert_eq!(v[Axis::Z], 3.0);
+    assert_eq!(Axis::ALL.map(|axis| v[axis]), [1.0, 5.0, 3.0]);
+}
+
+#[test]
+fn should_sum_vectors() {
+    let vectors = [Vector::new(1.0, 2.0, 3.0), Vector::new(-1.0, 0.5, 1.0)];
+
+    assert_eq!(vectors.iter().sum::<Vector>(), Vector::new(0.0, 2.5, 4.0));
+    assert_eq!(
+        vectors.into_iter().sum::<Vector>(),
+        Vector::new(0.0, 2.5, 4.0)
+    );
+    assert_eq!(
+        Vec::<Vector>::new().into_iter().sum::<Vector>(),
+        Vector::new(0.0, 0.0, 0.0)
+    );
+}
+
+#[test]
+fn should_normalize_in_single_precision() {
+    let v = Vector::new(1.0_f32, 2.0, 3.0).normalize();
+
+    assert_eq!(v, Vector::new(0.26726, 0.53452, 0.80178));
+    assert_eq!(v.cast::<f64>(), Vector::new(1.0, 2.0, 3.0).normalize());
+    assert_ne!(v, Vector::new(0.2675, 0.53452, 0.80178));
+}
+
+#[test]
+fn should_compare_vectors_with_chosen_tolerance() {
+    let a = Vector::new(1.0, 0.0, -1.0);
+    let b = Vector::new(1.0, 1e-3, -1.0);
+
+    assert!(a.abs_diff_eq(&b, 0.01));
+    assert!(!a.abs_diff_eq(&b, 1e-4));
+    assert!(!a.exact_eq(&Vector::new(1.0, 1e-9, -1.0)));
+    assert!(a.exact_eq(&Vector::new(1.0, -0.0, -1.0)));
+}
@@ -0,0 +1,356 @@
+use crate::color::Color;
+use crate::intersection::{hit, Computations, Intersection};
+use crate::light::Light;
+use crate::material::lighting;
+use crate::point::Point;
+use crate::ray::Ray;
+use crate::sphere::Sphere;
+use crate::vector::Vector;
+
+/// How many reflections `color_at` follows before it stops recursing.
+pub const MAX_DEPTH: usize = 5;
+
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    pub objects: Vec<Sphere>,
+    pub lights: Vec<Light>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut intersections = self
+            .objects
+            .iter()
+            .flat_map(|object| object.intersect(ray))
+            .collect::<Vec<Intersection>>();
+        intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
+        intersections
+    }
+
+    /// Sums the contribution of every light, each with its own shadow test,
+    /// and adds what the surface reflects, following at most `remaining`
+    /// more bounces.
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
+        let surface = self
+            .lights
+            .iter()
+            .map(|light| {
+                let intensity = light
+                    .intensity_at(comps.over_point, |point, direction, distance| {
+                        self.is_occluded(point, direction, distance)
+                    });
+                lighting(
+                    &comps.object.material,
+                    light,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    intensity,
+                )
+            })
+            .fold(Color::new(0.0, 0.0, 0.0), |sum, color| sum + color);
+
+        surface + self.reflected_color(comps, remaining)
+    }
+
+    pub fn color_at(&self, ray: &Ray) -> Color {
+        self.color_at_depth(ray, MAX_DEPTH)
+    }
+
+    /// Like `color_at`, but follows at most `remaining` reflections.
+    pub fn color_at_depth(&self, ray: &Ray, remaining: usize) -> Color {
+        let intersections = self.intersect(ray);
+        match hit(&intersections) {
+            Some(intersection) => {
+                self.shade_hit(&intersection.prepare_computations(ray), remaining)
+            }
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let reflective = comps.object.material.reflective;
+        if remaining == 0 || reflective == 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let ray = Ray::new(comps.over_point, comps.reflectv);
+        self.color_at_depth(&ray, remaining - 1) * reflective
+    }
+
+    /// Whether any object lies between `point` and `distance` along `direction`.
+    pub fn is_occluded(&self, point: Point, direction: Vector, distance: f64) -> bool {
+        let ray = Ray::new(point, direction);
+        let intersections = self.intersect(&ray);
+        matches!(hit(&intersections), Some(intersection) if intersection.t < distance)
+    }
+
+    /// Whether `light` is completely hidden from `point`.
+    pub fn is_shadowed(&self, light: &Light, point: Point) -> bool {
+        light.intensity_at(point, |point, direction, distance| {
+            self.is_occluded(point, direction, distance)
+        }) == 0.0
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::light::PointLight;
+    use crate::material::Material;
+    use crate::matrix::Matrix;
+
+    fn white() -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    /// The two concentric spheres and single light used throughout the tests.
+    pub fn default_world() -> World {
+        let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let outer = Sphere::new(
+            Matrix::identity(),
+            Material {
+                color: Color::new(0.8, 1.0, 0.6),
+                diffuse: 0.7,
+                specular: 0.2,
+                ..Material::default()
+            },
+        );
+        let inner = Sphere::new(Matrix::scaling(0.5, 0.5, 0.5), Material::default());
+
+        World {
+            objects: vec![outer, inner],
+            lights: vec![Light::from(light)],
+        }
+    }
+
+    #[test]
+    fn should_create_an_empty_world() {
+        let world = World::new();
+
+        assert!(world.objects.is_empty());
+        assert!(world.lights.is_empty());
+    }
+
+    #[test]
+    fn should_intersect_world_with_a_ray() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        let xs = world.intersect(&ray);
+
+        let ts = xs.iter().map(|x| x.t).collect::<Vec<f64>>();
+        assert_eq!(ts, vec![4.0, 4.5, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn should_shade_an_intersection() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(4.0, &world.objects[0]);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.38066, 0.47583, 0.2855)
+        );
+    }
+
+    #[test]
+    fn should_shade_an_intersection_from_the_inside() {
+        let mut world = default_world();
+        world.lights = vec![Light::from(PointLight::new(
+            Point::new(0.0, 0.25, 0.0),
+            white(),
+        ))];
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(0.5, &world.objects[1]);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.90498, 0.90498, 0.90498)
+        );
+    }
+
+    #[test]
+    fn should_shade_an_intersection_in_shadow() {
+        let mut world = World::new();
+        world.lights = vec![Light::from(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            white(),
+        ))];
+        world.objects = vec![
+            Sphere::default(),
+            Sphere::new(Matrix::translation(0.0, 0.0, 10.0), Material::default()),
+        ];
+        let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(4.0, &world.objects[1]);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(0.1, 0.1, 0.1)
+        );
+    }
+
+    #[test]
+    fn should_return_black_when_a_ray_misses() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+        assert_eq!(world.color_at(&ray), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_return_color_when_a_ray_hits() {
+        let world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        assert_eq!(world.color_at(&ray), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn should_use_hit_behind_the_ray_origin() {
+        let mut world = default_world();
+        world.objects[0].material.ambient = 1.0;
+        world.objects[1].material.ambient = 1.0;
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.75), Vector::new(0.0, 0.0, -1.0));
+
+        assert_eq!(world.color_at(&ray), world.objects[1].material.color);
+    }
+
+    #[test]
+    fn should_find_shadows_per_light() {
+        let world = default_world();
+        let light = &world.lights[0];
+
+        assert!(!world.is_shadowed(light, Point::new(0.0, 10.0, 0.0)));
+        assert!(world.is_shadowed(light, Point::new(10.0, -10.0, 10.0)));
+        assert!(!world.is_shadowed(light, Point::new(-20.0, 20.0, -20.0)));
+        assert!(!world.is_shadowed(light, Point::new(-2.0, 2.0, -2.0)));
+    }
+
+    #[test]
+    fn should_sum_contributions_of_every_light() {
+        let mut world = default_world();
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let single = world.color_at(&ray);
+
+        world.lights.push(world.lights[0]);
+        let double = world.color_at(&ray);
+
+        assert_eq!(double, single * 2.0);
+    }
+
+    #[test]
+    fn should_shadow_each_light_independently() {
+        let mut world = World::new();
+        world.objects = vec![
+            Sphere::default(),
+            Sphere::new(Matrix::translation(0.0, 0.0, 10.0), Material::default()),
+        ];
+        // the key light sits behind both spheres, the fill light faces the hit
+        let key = Light::from(PointLight::new(Point::new(0.0, 0.0, -10.0), white()));
+        let fill = Light::from(PointLight::new(
+            Point::new(0.0, 0.0, 20.0),
+            Color::new(0.5, 0.5, 0.5),
+        ));
+        world.lights = vec![key, fill];
+        let ray = Ray::new(Point::new(0.0, 0.0, 15.0), Vector::new(0.0, 0.0, -1.0));
+
+        let intersections = world.intersect(&ray);
+        let comps = hit(&intersections).unwrap().prepare_computations(&ray);
+
+        assert!(world.is_shadowed(&key, comps.over_point));
+        assert!(!world.is_shadowed(&fill, comps.over_point));
+        // ambient of both lights, diffuse and specular of the fill light only
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            Color::new(1.05, 1.05, 1.05)
+        );
+    }
+
+    #[test]
+    fn should_not_reflect_from_nonreflective_material() {
+        let mut world = default_world();
+        world.objects[1].material.ambient = 1.0;
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(1.0, &world.objects[1]);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(
+            world.reflected_color(&comps, MAX_DEPTH),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn should_reflect_the_scene_in_reflective_material() {
+        let mut world = default_world();
+        world.objects[0].material.reflective = 0.5;
+        world.objects.push(Sphere::new(
+            Matrix::translation(0.0, 0.0, -3.0),
+            Material {
+                ambient: 1.0,
+                ..Material::default()
+            },
+        ));
+        // the ray bounces straight back off the outer sphere into the new one
+        let ray = Ray::new(Point::new(0.0, 0.0, -1.5), Vector::new(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(0.5, &world.objects[0]);
+
+        let comps = intersection.prepare_computations(&ray);
+        let reflected = world.reflected_color(&comps, MAX_DEPTH);
+
+        assert_eq!(
+            reflected,
+            world.color_at_depth(&Ray::new(comps.over_point, comps.reflectv), MAX_DEPTH - 1) * 0.5
+        );
+        assert!(!reflected.is_black());
+        assert_eq!(
+            world.shade_hit(&comps, MAX_DEPTH),
+            world.shade_hit(&comps, 0) + reflected
+        );
+    }
+
+    #[test]
+    fn should_stop_reflecting_at_maximum_depth() {
+        let mut world = default_world();
+        world.objects[0].material.reflective = 0.5;
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let intersection = Intersection::new(4.0, &world.objects[0]);
+
+        let comps = intersection.prepare_computations(&ray);
+
+        assert_eq!(world.reflected_color(&comps, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn should_terminate_between_mutually_reflective_surfaces() {
+        let mirror = Material {
+            reflective: 1.0,
+            ..Material::default()
+        };
+        let mut world = World::new();
+        world.lights = vec![Light::from(PointLight::new(
+            Point::new(0.0, 0.0, 0.0),
+            white(),
+        ))];
+        // a ray inside a mirrored sphere keeps bouncing off its inner wall
+        world.objects = vec![Sphere::new(Matrix::scaling(2.0, 2.0, 2.0), mirror)];
+        let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+
+        let color = world.color_at(&ray);
+
+        assert!(!color.is_black());
+    }
+}